@@ -1,5 +1,3 @@
-use std::cell::Cell;
-
 use serde::{Deserialize, Serialize};
 
 use dmc::Dmc;
@@ -8,6 +6,8 @@ use pulse::sweep::Mode;
 use pulse::Pulse;
 use triangle::Triangle;
 
+use crate::bus::irq::{IrqLine, IrqSource};
+use crate::region::Region;
 use crate::util::bit::Bit;
 
 mod debug;
@@ -27,6 +27,11 @@ const CYCLES: [[usize; 6]; 2] = [
     [7457, 14913, 22371, 29829, 37281, 37282],
 ];
 
+const PAL_CYCLES: [[usize; 6]; 2] = [
+    [8313, 16627, 24939, 33252, 33253, 33254],
+    [8313, 16627, 24939, 33253, 41565, 41566],
+];
+
 #[derive(Serialize, Deserialize, Clone)]
 enum SequenceMode {
     FourStep,
@@ -36,7 +41,6 @@ enum SequenceMode {
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Apu {
     pub dmc: Dmc,
-    pub interrupt: Cell<bool>,
     clockrate: u32,
     frame_counter: usize,
     irq_inhibit: bool,
@@ -48,23 +52,27 @@ pub struct Apu {
     triangle: Triangle,
     request_cycles: usize,
     reset_requested: bool,
+    #[serde(default)]
+    region: Region,
 }
 
 impl Apu {
-    pub fn new(clockrate: u32) -> Self {
+    pub fn new(region: Region) -> Self {
+        let clockrate = region.clockrate();
+
         Self {
-            dmc: Dmc::new(),
-            interrupt: Cell::new(false),
+            dmc: Dmc::new(region),
             clockrate,
             cycles: 0,
             frame_counter: 0,
             irq_inhibit: false,
             mode: SequenceMode::FourStep,
-            noise: Noise::new(),
+            noise: Noise::new(region),
             pulse1: Pulse::new(Mode::OnesComplement, clockrate),
             pulse2: Pulse::new(Mode::TwosComplement, clockrate),
             request_cycles: 0,
             reset_requested: false,
+            region,
             triangle: Triangle::new(clockrate),
         }
     }
@@ -80,19 +88,19 @@ impl Apu {
         [pulse1, pulse2, triangle, noise, dmc, mixed]
     }
 
-    pub fn read(&self, addr: u16) -> u8 {
+    pub fn read(&self, addr: u16, irq: &IrqLine) -> u8 {
         match addr {
             0x4000..=0x4014 => 0,
             0x4015 => {
-                let dmc_interrupt = self.dmc.irq as u8;
-                let frame_interrupt = self.interrupt.get() as u8;
+                let dmc_interrupt = irq.is_asserted(IrqSource::Dmc) as u8;
+                let frame_interrupt = irq.is_asserted(IrqSource::FrameCounter) as u8;
                 let dmc_active = (self.dmc.length > 0) as u8;
                 let noise_len = (self.noise.length > 0) as u8;
                 let triangle_len = (self.triangle.length > 0) as u8;
                 let pulse1_len = (self.pulse1.length > 0) as u8;
                 let pulse2_len = (self.pulse2.length > 0) as u8;
 
-                self.interrupt.replace(false);
+                irq.acknowledge(IrqSource::FrameCounter);
 
                 dmc_interrupt << 7
                     | frame_interrupt << 6
@@ -110,7 +118,7 @@ impl Apu {
         self.clockrate = clockrate;
     }
 
-    pub fn tick(&mut self) {
+    pub fn tick(&mut self, irq: &IrqLine) {
         if self.reset_requested {
             if self.request_cycles == 0 {
                 self.update_envs();
@@ -138,14 +146,19 @@ impl Apu {
             SequenceMode::FiveStep => 1,
         };
 
-        if self.cycles == CYCLES[mode_idx][self.frame_counter] {
-            self.tock();
+        let cycles = match self.region {
+            Region::Pal => &PAL_CYCLES,
+            Region::Ntsc | Region::Dendy => &CYCLES,
+        };
+
+        if self.cycles == cycles[mode_idx][self.frame_counter] {
+            self.tock(irq);
         }
 
         self.cycles += 1;
     }
 
-    pub fn tock(&mut self) {
+    pub fn tock(&mut self, irq: &IrqLine) {
         match self.mode {
             SequenceMode::FourStep => {
                 match self.frame_counter {
@@ -164,7 +177,7 @@ impl Apu {
                         self.triangle.dec_lin();
                     }
                     3 => {
-                        self.interrupt.replace(!self.irq_inhibit);
+                        self.assert_frame_irq(irq);
                     }
                     4 => {
                         self.update_lens();
@@ -172,10 +185,10 @@ impl Apu {
                         self.update_envs();
                         self.triangle.dec_lin();
 
-                        self.interrupt.replace(!self.irq_inhibit);
+                        self.assert_frame_irq(irq);
                     }
                     5 => {
-                        self.interrupt.replace(!self.irq_inhibit);
+                        self.assert_frame_irq(irq);
 
                         self.cycles = 0;
                     }
@@ -219,6 +232,12 @@ impl Apu {
         }
     }
 
+    fn assert_frame_irq(&self, irq: &IrqLine) {
+        if !self.irq_inhibit {
+            irq.assert(IrqSource::FrameCounter);
+        }
+    }
+
     fn mix(audio_samples: [f32; 5]) -> f32 {
         let [pulse1, pulse2, triangle, noise, dmc] = audio_samples;
 
@@ -256,7 +275,7 @@ impl Apu {
         self.noise.update_env();
     }
 
-    pub fn write(&mut self, addr: u16, data: u8) {
+    pub fn write(&mut self, addr: u16, data: u8, irq: &IrqLine) {
         match addr {
             0x4000 => self.pulse1.write(0, data),
             0x4001 => self.pulse1.write(1, data),
@@ -274,7 +293,14 @@ impl Apu {
             0x400D => {}
             0x400E => self.noise.write(2, data),
             0x400F => self.noise.write(3, data),
-            0x4010 => self.dmc.write(0, data),
+            0x4010 => {
+                self.dmc.write(0, data);
+
+                // Clearing the IRQ enable flag also clears a pending DMC interrupt
+                if !data.bit(7) {
+                    irq.acknowledge(IrqSource::Dmc);
+                }
+            }
             0x4011 => self.dmc.write(1, data),
             0x4012 => self.dmc.write(2, data),
             0x4013 => self.dmc.write(3, data),
@@ -285,7 +311,7 @@ impl Apu {
                 let pulse2_enabled = data.bit(1);
                 let pulse1_enabled = data.bit(0);
 
-                self.dmc.irq = false;
+                irq.acknowledge(IrqSource::Dmc);
 
                 self.pulse1.enable(pulse1_enabled);
                 self.pulse2.enable(pulse2_enabled);
@@ -315,7 +341,7 @@ impl Apu {
                 }
 
                 if irq_inhibit {
-                    self.interrupt.replace(false);
+                    irq.acknowledge(IrqSource::FrameCounter);
                 }
             }
             _ => {}
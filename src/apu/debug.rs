@@ -19,7 +19,6 @@ impl DebugInfo for Apu {
             ui.separator();
             ui.vertical(|ui| {
                 ui.label("Frame counter");
-                let _ = ui.radio(self.interrupt.get(), "IRQ");
                 ui.label(format!(
                     "Mode: {}",
                     if matches!(self.mode, SequenceMode::FourStep) {
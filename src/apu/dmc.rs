@@ -2,16 +2,21 @@ use crate::gui::DebugInfo;
 use egui::Ui;
 use serde::{Deserialize, Serialize};
 
+use crate::bus::irq::{IrqLine, IrqSource};
+use crate::region::Region;
 use crate::util::bit::Bit;
 
 const RATES: [u16; 16] = [
     428, 380, 340, 320, 286, 254, 226, 214, 190, 160, 142, 128, 106, 84, 72, 54,
 ];
 
+const PAL_RATES: [u16; 16] = [
+    398, 354, 316, 298, 276, 236, 210, 198, 176, 148, 132, 118, 98, 78, 66, 50,
+];
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Dmc {
     pub addr: u16,
-    pub irq: bool,
     pub(super) length: u16,
     addr_reload: u16,
     bits_remaining: u8,
@@ -20,16 +25,17 @@ pub struct Dmc {
     length_reload: u16,
     loop_flag: bool,
     output: u8,
+    #[serde(default)]
+    region: Region,
     sample_buf: Option<u8>,
     timer: u16,
     timer_reload: u16,
 }
 
 impl Dmc {
-    pub fn new() -> Self {
+    pub fn new(region: Region) -> Self {
         Self {
             addr: 0,
-            irq: false,
             length: 0,
             addr_reload: 0,
             bits_remaining: 0,
@@ -38,6 +44,7 @@ impl Dmc {
             length_reload: 0,
             loop_flag: false,
             output: 0,
+            region,
             sample_buf: None,
             timer: 0,
             timer_reload: 0,
@@ -53,10 +60,14 @@ impl Dmc {
 
         if !self.enabled {
             self.length = 0;
+        } else if self.length == 0 {
+            // Enabling a finished (or never started) sample restarts it
+            self.addr = self.addr_reload;
+            self.length = self.length_reload;
         }
     }
 
-    pub fn fill_buffer(&mut self, data: u8) {
+    pub fn fill_buffer(&mut self, data: u8, irq: &IrqLine) {
         self.sample_buf = Some(data);
 
         if self.addr == 0xFFFF {
@@ -72,7 +83,7 @@ impl Dmc {
                 self.addr = self.addr_reload;
                 self.length = self.length_reload;
             } else if self.irq_enabled {
-                self.irq = true;
+                irq.assert(IrqSource::Dmc);
             }
         }
     }
@@ -131,7 +142,12 @@ impl Dmc {
 
                 self.irq_enabled = irq_enabled;
                 self.loop_flag = loop_flag;
-                self.timer_reload = RATES[rate_idx] / 2;
+                let rates = match self.region {
+                    Region::Pal => &PAL_RATES,
+                    Region::Ntsc | Region::Dendy => &RATES,
+                };
+
+                self.timer_reload = rates[rate_idx] / 2;
             }
             1 => {
                 let load = data.bits_abs(0, 6);
@@ -142,14 +158,12 @@ impl Dmc {
                 let data = u16::from(data);
                 let address = 0xC000 + (data * 64);
 
-                self.addr = address;
                 self.addr_reload = address;
             }
             3 => {
                 let data = u16::from(data);
                 let length = data * 16 + 1;
 
-                self.length = length;
                 self.length_reload = length;
             }
             _ => unreachable!(),
@@ -164,7 +178,6 @@ impl DebugInfo for Dmc {
             ui.vertical(|ui| {
                 let _ = ui.radio(self.enabled, "Enabled");
                 let _ = ui.radio(self.irq_enabled, "IRQ Enabled");
-                let _ = ui.radio(self.irq, "IRQ asserted");
                 let _ = ui.radio(self.loop_flag, "Loop");
                 ui.label(format!("Rate: {} cycles per sample", self.timer_reload));
                 ui.label(format!(
@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use crate::gui::DebugInfo;
 use lfsr::Lfsr;
 
+use crate::region::Region;
 use crate::util::bit::Bit;
 
 use super::envelope::Envelope;
@@ -15,6 +16,10 @@ const PERIODS: [u16; 16] = [
     4, 8, 16, 32, 64, 96, 128, 160, 202, 254, 380, 508, 762, 1016, 2034, 4068,
 ];
 
+const PAL_PERIODS: [u16; 16] = [
+    4, 8, 14, 30, 60, 88, 118, 148, 188, 236, 354, 472, 708, 944, 1890, 3778,
+];
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Noise {
     pub(super) length: u8,
@@ -24,11 +29,13 @@ pub struct Noise {
     lfsr: Lfsr,
     mode: bool,
     reload: u16,
+    #[serde(default)]
+    region: Region,
     timer: u16,
 }
 
 impl Noise {
-    pub fn new() -> Self {
+    pub fn new(region: Region) -> Self {
         Self {
             length: 0,
             enabled: false,
@@ -37,6 +44,7 @@ impl Noise {
             lfsr: Lfsr::new(),
             mode: false,
             reload: 0,
+            region,
             timer: 0,
         }
     }
@@ -95,7 +103,12 @@ impl Noise {
                 let reload_idx = data.bits_abs(0, 3);
 
                 self.mode = mode;
-                self.reload = PERIODS[reload_idx as usize];
+                let periods = match self.region {
+                    Region::Pal => &PAL_PERIODS,
+                    Region::Ntsc | Region::Dendy => &PERIODS,
+                };
+
+                self.reload = periods[reload_idx as usize];
             }
             3 => {
                 let len_idx = data.bits(3, 7);
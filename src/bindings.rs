@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::controller::Btn;
+
+/// An emulator function that can be bound to a key or gamepad button
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    Quit,
+    SaveState,
+    LoadState,
+    /// Picks save state slot 0-8 for saving and loading
+    SelectSlot(usize),
+    Pause,
+    FrameAdvance,
+    Reset,
+    Mute,
+    /// Starts recording the inputs of both players into macro slot `n`, or stops if it's
+    /// already recording
+    RecordMacro(usize),
+    /// Plays back macro slot `n` on top of what the players are pressing
+    PlayMacro(usize),
+}
+
+/// What each input of one player does. Keys, buttons and axes are named as SDL names them, e.g.
+/// `"Left Shift"` for a key, `"a"` or `"dpup"` for a button and `"leftx"` for an axis. Fields
+/// left out of a profile get the gamepad's usual layout and no keys.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Profile {
+    pub keys: BTreeMap<String, Btn>,
+    /// Buttons of the player's gamepad
+    pub buttons: BTreeMap<String, Btn>,
+    /// Axes of the player's gamepad, with the NES buttons for the negative and positive
+    /// directions
+    pub axes: BTreeMap<String, [Btn; 2]>,
+    /// Keys that press a button repeatedly while held
+    pub turbo_keys: BTreeMap<String, Btn>,
+    /// Gamepad buttons that press a button repeatedly while held
+    pub turbo_buttons: BTreeMap<String, Btn>,
+    /// Presses per second of turbo buttons, counted in emulated frames
+    pub turbo_rate: u32,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            keys: BTreeMap::new(),
+            buttons: [
+                ("a", Btn::A),
+                ("b", Btn::B),
+                ("back", Btn::Select),
+                ("start", Btn::Start),
+                ("dpup", Btn::Up),
+                ("dpdown", Btn::Down),
+                ("dpleft", Btn::Left),
+                ("dpright", Btn::Right),
+            ]
+            .map(|(name, button)| (String::from(name), button))
+            .into(),
+            axes: [
+                ("leftx", [Btn::Left, Btn::Right]),
+                ("lefty", [Btn::Up, Btn::Down]),
+            ]
+            .map(|(name, buttons)| (String::from(name), buttons))
+            .into(),
+            turbo_keys: BTreeMap::new(),
+            turbo_buttons: [("x", Btn::B), ("y", Btn::A)]
+                .map(|(name, button)| (String::from(name), button))
+                .into(),
+            turbo_rate: 15,
+        }
+    }
+}
+
+impl Profile {
+    fn with_keys(mut self, keys: [(&str, Btn); 8], turbo_keys: [(&str, Btn); 2]) -> Self {
+        self.keys = keys
+            .map(|(name, button)| (String::from(name), button))
+            .into();
+        self.turbo_keys = turbo_keys
+            .map(|(name, button)| (String::from(name), button))
+            .into();
+
+        self
+    }
+}
+
+/// The bindings file: emulator actions, and a profile for each of the two players
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Bindings {
+    /// Keys for actions
+    pub keys: BTreeMap<String, Action>,
+    /// Buttons for actions, on any gamepad
+    pub buttons: BTreeMap<String, Action>,
+    pub players: [Profile; 2],
+}
+
+impl Bindings {
+    /// Reads a bindings file. Sections it leaves out keep their defaults.
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let mut keys: BTreeMap<String, Action> = [
+            ("Escape", Action::Quit),
+            ("F5", Action::SaveState),
+            ("F7", Action::LoadState),
+            ("P", Action::Pause),
+            ("F", Action::FrameAdvance),
+            ("R", Action::Reset),
+            ("M", Action::Mute),
+            ("F9", Action::RecordMacro(0)),
+            ("F10", Action::PlayMacro(0)),
+        ]
+        .map(|(name, action)| (String::from(name), action))
+        .into();
+
+        for slot in 0..9 {
+            keys.insert((slot + 1).to_string(), Action::SelectSlot(slot));
+        }
+
+        Self {
+            keys,
+            buttons: BTreeMap::new(),
+            players: [
+                Profile::default().with_keys(
+                    [
+                        ("X", Btn::A),
+                        ("Z", Btn::B),
+                        ("Right Shift", Btn::Select),
+                        ("Return", Btn::Start),
+                        ("Up", Btn::Up),
+                        ("Down", Btn::Down),
+                        ("Left", Btn::Left),
+                        ("Right", Btn::Right),
+                    ],
+                    [("C", Btn::B), ("V", Btn::A)],
+                ),
+                Profile::default().with_keys(
+                    [
+                        ("H", Btn::A),
+                        ("G", Btn::B),
+                        ("T", Btn::Select),
+                        ("Y", Btn::Start),
+                        ("W", Btn::Up),
+                        ("S", Btn::Down),
+                        ("A", Btn::Left),
+                        ("D", Btn::Right),
+                    ],
+                    [("B", Btn::B), ("N", Btn::A)],
+                ),
+            ],
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use serde::{Deserialize, Serialize};
 use serde_big_array::BigArray;
 
@@ -5,39 +7,130 @@ use crate::apu::Apu;
 use crate::cartridge::{Cartridge, MapperType};
 use crate::controller::Controller;
 use crate::ppu::Ppu;
+use crate::region::Region;
+use crate::util::{load_ram, save_ram};
+use irq::IrqLine;
 
+pub mod irq;
 pub mod mapper;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Bus {
+    /// CPU cycles since power on
+    #[serde(default)]
+    pub cycle: u64,
     pub dma_interrupt: Option<u8>,
+    #[serde(default)]
+    pub irq: IrqLine,
     apu: Apu,
     controller1: Controller,
+    #[serde(default)]
+    controller2: Controller,
+    // The battery-backed part of `prg_ram`, which is what goes in the .sav file. It belongs to
+    // the cartridge, so save states leave it out.
+    #[serde(skip)]
+    pub nvram: Range<usize>,
     ppu: Ppu,
+    // PPU dots owed to the PPU, in fractions of a CPU cycle
+    #[serde(default)]
+    ppu_phase: u32,
+    #[serde(default)]
+    prg_ram: Vec<u8>,
     prg_rom: Vec<u8>,
+    #[serde(default)]
+    region: Region,
     #[serde(with = "BigArray")]
     ram: [u8; 0x800],
 }
 
 impl Bus {
-    pub fn new(mut cartridge: Cartridge, mapper_type: MapperType, clockrate: u32) -> Self {
+    pub fn new(mut cartridge: Cartridge, mapper_type: MapperType, region: Region) -> Self {
         let prg_rom = cartridge.prg_rom.take().unwrap_or_default();
 
-        assert!(
-            prg_rom.is_empty() || prg_rom.len() == 0x4000 || prg_rom.len() == 0x8000,
-            "NROM PRGROM size is not 16K or 32K"
-        );
+        let trainer = cartridge.trainer.take();
+
+        let prg_ram_size = if trainer.is_some() {
+            cartridge.total_prg_ram_size().max(0x2000)
+        } else {
+            cartridge.total_prg_ram_size()
+        };
+
+        let mut prg_ram = vec![0; prg_ram_size];
+
+        // NES 2.0 headers count volatile RAM first, so NVRAM is whatever comes after it
+        let nvram = if cartridge.battery {
+            cartridge.prg_ram_size.min(prg_ram_size)..prg_ram_size
+        } else {
+            0..0
+        };
+
+        // Trainers are mapped to $7000-$71FF
+        if let Some(trainer) = trainer {
+            prg_ram[0x1000..0x1200].copy_from_slice(&trainer);
+        }
 
         Self {
+            cycle: 0,
             dma_interrupt: None,
-            apu: Apu::new(clockrate),
+            irq: IrqLine::default(),
+            apu: Apu::new(region),
             controller1: Controller::new(),
-            ppu: Ppu::new(cartridge, mapper_type),
+            controller2: Controller::new(),
+            nvram,
+            ppu: Ppu::new(cartridge, mapper_type, region),
+            ppu_phase: 0,
+            prg_ram,
             prg_rom,
+            region,
             ram: [0; 0x800],
         }
     }
 
+    /// Advances the PPU and APU by one CPU cycle
+    pub fn tick(&mut self) {
+        let (dots, cycles) = self.region.ppu_dots_per_cycle();
+
+        // PAL runs 3.2 dots per cycle, so every fifth cycle gets a fourth dot
+        self.ppu_phase += dots;
+
+        while self.ppu_phase >= cycles {
+            self.ppu_phase -= cycles;
+
+            self.ppu.tick(&self.irq);
+        }
+
+        self.apu.tick(&self.irq);
+
+        self.cycle += 1;
+    }
+
+    /// Fills the battery-backed RAM from the .sav file next to `filename`
+    pub fn load_battery_ram(&mut self, filename: &str) {
+        if !self.nvram.is_empty() {
+            load_ram(filename, &mut self.prg_ram[self.nvram.clone()]);
+        }
+    }
+
+    /// Writes the battery-backed RAM to the .sav file next to `filename`
+    pub fn save_battery_ram(&self, filename: &str) {
+        if !self.nvram.is_empty() {
+            save_ram(filename, &self.prg_ram[self.nvram.clone()]);
+        }
+    }
+
+    /// The controller plugged into `port`, 0 for $4016 and 1 for $4017
+    pub fn controller(&mut self, port: usize) -> &mut Controller {
+        match port {
+            0 => &mut self.controller1,
+            1 => &mut self.controller2,
+            _ => panic!("the NES has two controller ports, not {}", port + 1),
+        }
+    }
+
+    pub fn fill_dmc_buffer(&mut self, data: u8) {
+        self.apu.dmc.fill_buffer(data, &self.irq);
+    }
+
     fn read_u8(&self, addr: u16) -> u8 {
         match addr {
             0x0000..=0x1FFF => {
@@ -50,12 +143,19 @@ impl Bus {
 
                 self.ppu.read_reg(addr as u8)
             }
-            0x4000..=0x4015 => self.apu.read(addr),
+            0x4000..=0x4015 => self.apu.read(addr, &self.irq),
             0x4016 => self.controller1.read(),
-            // TODO Second controller
-            0x4017 => 0,
+            0x4017 => self.controller2.read(),
             0x4018..=0x5FFF => 0,
-            0x6000..=0x7FFF => 0,
+            0x6000..=0x7FFF => {
+                if self.prg_ram.is_empty() {
+                    return 0;
+                }
+
+                let offset = (addr - 0x6000) as usize % self.prg_ram.len();
+
+                self.prg_ram[offset]
+            }
             0x8000..=0xFFFF => {
                 let addr = addr % 0x8000;
 
@@ -87,11 +187,23 @@ impl Bus {
             0x4014 => {
                 self.dma_interrupt.replace(data);
             }
-            0x4000..=0x4015 => self.apu.write(addr, data),
-            0x4016 => self.controller1.write(data),
-            0x4017 => self.apu.write(addr, data),
+            0x4000..=0x4015 => self.apu.write(addr, data, &self.irq),
+            // Both controllers share the strobe line
+            0x4016 => {
+                self.controller1.write(data);
+                self.controller2.write(data);
+            }
+            0x4017 => self.apu.write(addr, data, &self.irq),
             0x4018..=0x5FFF => {}
-            0x6000..=0x7FFF => {}
+            0x6000..=0x7FFF => {
+                if self.prg_ram.is_empty() {
+                    return;
+                }
+
+                let offset = (addr - 0x6000) as usize % self.prg_ram.len();
+
+                self.prg_ram[offset] = data;
+            }
             0x8000..=0xFFFF => {}
         }
     }
@@ -0,0 +1,62 @@
+use std::cell::Cell;
+
+use egui::Ui;
+use serde::{Deserialize, Serialize};
+
+use crate::gui::DebugInfo;
+
+/// Everything that can pull the CPU's /IRQ input low
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IrqSource {
+    FrameCounter,
+    Dmc,
+    /// Cartridge hardware, such as the MMC3 scanline counter
+    Mapper,
+}
+
+impl IrqSource {
+    const ALL: [IrqSource; 3] = [IrqSource::FrameCounter, IrqSource::Dmc, IrqSource::Mapper];
+
+    fn mask(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// The open-collector /IRQ line. Each source asserts and acknowledges its own signal and the CPU
+/// sees the wired-OR of all of them, so the line stays low until every source has been
+/// acknowledged.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct IrqLine {
+    sources: Cell<u8>,
+}
+
+impl IrqLine {
+    pub fn assert(&self, source: IrqSource) {
+        self.sources.set(self.sources.get() | source.mask());
+    }
+
+    pub fn acknowledge(&self, source: IrqSource) {
+        self.sources.set(self.sources.get() & !source.mask());
+    }
+
+    pub fn is_asserted(&self, source: IrqSource) -> bool {
+        self.sources.get() & source.mask() != 0
+    }
+
+    /// Whether any source is holding the line
+    pub fn level(&self) -> bool {
+        self.sources.get() != 0
+    }
+}
+
+impl DebugInfo for IrqLine {
+    fn print(&self, ui: &mut Ui) {
+        ui.vertical(|ui| {
+            ui.label("IRQ line");
+
+            for source in IrqSource::ALL {
+                let _ = ui.radio(self.is_asserted(source), format!("{source:?}"));
+            }
+        });
+    }
+}
@@ -1,5 +1,6 @@
 #![allow(clippy::large_enum_variant)]
 
+#[cfg(feature = "sdl")]
 use std::any::Any;
 use std::ops::Range;
 
@@ -17,7 +18,7 @@ pub use mockbus::MockBus;
 
 use crate::apu::Apu;
 use crate::bus::Bus;
-use crate::cartridge::Cartridge;
+use crate::cartridge::{Cartridge, Mirroring};
 use crate::controller::Controller;
 use crate::gui::DebugInfo;
 use crate::ppu::Ppu;
@@ -36,22 +37,40 @@ pub type Setting = (usize, Range<i32>);
 pub trait MapperTrait: DebugInfo {
     fn apu(&mut self) -> &mut Apu;
 
+    #[cfg(feature = "sdl")]
     fn as_any(&self) -> &dyn Any;
 
     fn bus(&mut self) -> &mut Bus;
 
-    fn controller(&mut self) -> &mut Controller;
+    fn controller(&mut self, port: usize) -> &mut Controller;
 
     fn memory(&self) -> &[u8];
 
+    /// Maps the four 1KiB nametable slots at $2000-$2FFF, for boards that decide where each
+    /// one goes rather than wiring up a fixed mirroring
+    fn map_nametables(&mut self, slots: [Nametable; 4]) {
+        self.ppu().set_nametables(slots);
+    }
+
+    /// The nametable slots the board currently selects, which save states don't keep
+    fn nametables(&self) -> [Nametable; 4] {
+        Nametable::mirrored(self.ppu_ref().mirroring)
+    }
+
     fn ppu(&mut self) -> &mut Ppu;
 
+    fn ppu_ref(&self) -> &Ppu;
+
+    #[cfg(feature = "sdl")]
     fn print_debug_info(&self, ui: &mut Ui) {
         self.print(ui);
     }
 
     fn read_u8(&self, addr: u16) -> u8;
 
+    /// Fills in what a save state from an older version leaves out, after it's deserialized
+    fn restore(&mut self) {}
+
     fn read_u16(&self, addr: u16) -> u16 {
         let l = self.read_u8(addr);
 
@@ -64,6 +83,7 @@ pub trait MapperTrait: DebugInfo {
 
     fn write_u8(&mut self, addr: u16, data: u8);
 
+    #[cfg(feature = "sdl")]
     fn write_u16(&mut self, addr: u16, data: u16) {
         let h = ((data & 0xFF00) >> 8) as u8;
 
@@ -91,6 +111,38 @@ impl DebugInfo for Mapper {
     fn print(&self, _ui: &mut Ui) {}
 }
 
+/// What one 1KiB nametable slot reads from and writes to
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Nametable {
+    /// The first 1KiB page of the console's nametable RAM (CIRAM)
+    #[default]
+    CiramA,
+    /// The second page of CIRAM
+    CiramB,
+    /// A 1KiB page of nametable RAM on the cartridge
+    CartRam(usize),
+    /// A 1KiB page of CHR, which can't be written through the nametables
+    ChrRom(usize),
+    /// Every tile reads as `tile` and every attribute byte as `attribute`, and writes are
+    /// ignored
+    Fill { tile: u8, attribute: u8 },
+}
+
+impl Nametable {
+    /// The slots a fixed mirroring wires up
+    pub fn mirrored(mirroring: Mirroring) -> [Nametable; 4] {
+        use Nametable::*;
+
+        match mirroring {
+            Mirroring::Horizontal => [CiramA, CiramA, CiramB, CiramB],
+            Mirroring::Vertical => [CiramA, CiramB, CiramA, CiramB],
+            Mirroring::OneScreenLowerBank | Mirroring::SingleScreen => [CiramA; 4],
+            Mirroring::OneScreenUpperBank => [CiramB; 4],
+            Mirroring::FourScreen => [CiramA, CiramB, CartRam(0), CartRam(1)],
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct BankSettings(Vec<Setting>);
 
@@ -4,6 +4,7 @@ use crate::bus::Bus;
 use crate::cartridge::MapperType;
 use crate::controller::Controller;
 use crate::gui::DebugInfo;
+use crate::region::Region;
 
 use super::*;
 
@@ -13,9 +14,9 @@ pub struct Mapper0 {
 }
 
 impl Mapper0 {
-    pub fn new(cartridge: Cartridge, clockrate: u32) -> Self {
+    pub fn new(cartridge: Cartridge, region: Region) -> Self {
         Self {
-            bus: Bus::new(cartridge, MapperType::Nrom, clockrate),
+            bus: Bus::new(cartridge, MapperType::Nrom, region),
         }
     }
 }
@@ -25,6 +26,7 @@ impl MapperTrait for Mapper0 {
         &mut self.bus.apu
     }
 
+    #[cfg(feature = "sdl")]
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -33,8 +35,8 @@ impl MapperTrait for Mapper0 {
         &mut self.bus
     }
 
-    fn controller(&mut self) -> &mut Controller {
-        &mut self.bus.controller1
+    fn controller(&mut self, port: usize) -> &mut Controller {
+        self.bus.controller(port)
     }
 
     fn memory(&self) -> &[u8] {
@@ -45,6 +47,10 @@ impl MapperTrait for Mapper0 {
         &mut self.bus.ppu
     }
 
+    fn ppu_ref(&self) -> &Ppu {
+        &self.bus.ppu
+    }
+
     fn read_u8(&self, addr: u16) -> u8 {
         self.bus.read_u8(addr)
     }
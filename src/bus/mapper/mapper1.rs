@@ -1,69 +1,82 @@
 use egui::Ui;
-use serde_big_array::BigArray;
 
 use crate::bus::Bus;
 use crate::cartridge::{MapperType, Mirroring};
 use crate::controller::Controller;
 use crate::gui::DebugInfo;
+use crate::region::Region;
 use crate::util::bit::Bit;
 use crate::util::shift_reg::ShiftRegister;
-use crate::util::{load_ram, save_ram, Config};
+use crate::util::Config;
 
 use super::*;
 
-#[derive(Serialize, Deserialize, PartialEq, Clone)]
-enum PrgRomMode {
-    Switch32kB,
-    FixFirstBank,
-    FixLastBank,
-}
-
-#[derive(Serialize, Deserialize, Clone)]
-enum ChrRomMode {
-    Switch8kB,
-    SwitchTwo4kB,
-}
+// SUROM and SXROM carry 512KiB of PRG ROM, twice what the PRG register can reach
+const OUTER_PRG_BANK_SIZE: usize = 0x40000;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Mapper1 {
     bank_settings: BankSettings,
     bus: Bus,
-    chr_rom_mode: ChrRomMode,
-    cur_bank: usize,
+    // $A000 and $C000
+    chr_banks: [u8; 2],
+    // $8000
+    control: u8,
     filename: String,
+    // CPU cycle of the last write to $8000-$FFFF
+    #[serde(default)]
+    last_write: Option<u64>,
+    // MMC1A always enables PRG RAM, MMC1B lets bit 4 of the PRG register disable it
+    #[serde(default)]
+    mmc1a: bool,
     num_banks: usize,
+    // $E000
+    prg_bank: u8,
     prg_rom: Vec<u8>,
-    prg_rom_mode: PrgRomMode,
-    ram_enabled: bool,
     shift_reg: ShiftRegister<u8, 5>,
-    #[serde(with = "BigArray")]
-    sram: [u8; 0x2000],
+    // PRG RAM of save states from before it moved to the bus
+    #[serde(default, skip_serializing)]
+    sram: Vec<u8>,
     writes: usize,
 }
 
 impl Mapper1 {
-    pub fn new(mut cartridge: Cartridge, config: &Config, clockrate: u32) -> Self {
+    pub fn new(mut cartridge: Cartridge, config: &Config, region: Region) -> Self {
         let prg_rom = cartridge.prg_rom.take().unwrap();
 
+        // Submapper 3 is the MMC1A
+        let mmc1a = cartridge.nes2 && cartridge.submapper == 3;
+
+        let mut bus = Bus::new(cartridge, MapperType::MMC1, region);
+
+        if !config.no_battery {
+            bus.load_battery_ram(&config.filename);
+        }
+
         let num_banks = prg_rom.len() / 0x4000;
 
-        Self {
+        let mut mapper = Self {
             bank_settings: BankSettings::new(vec![
                 (0, (0x8000..0xC000)),
                 (num_banks - 1, (0xC000..0x10000)),
             ]),
-            bus: Bus::new(cartridge, MapperType::MMC1, clockrate),
-            chr_rom_mode: ChrRomMode::Switch8kB,
-            cur_bank: 0,
-            sram: load_ram(&config.filename),
+            bus,
+            chr_banks: [0, 0],
+            control: 0x0C,
             filename: config.filename.clone(),
+            last_write: None,
+            mmc1a,
             num_banks,
+            prg_bank: 0,
             prg_rom,
-            prg_rom_mode: PrgRomMode::FixLastBank,
-            ram_enabled: true,
             shift_reg: ShiftRegister::new(),
+            sram: Vec::new(),
             writes: 0,
-        }
+        };
+
+        mapper.update_banks();
+
+        mapper
     }
 
     fn read_prg(&self, addr: u16) -> u8 {
@@ -75,129 +88,128 @@ impl Mapper1 {
             .find(|(_, addresses)| addresses.contains(&addr))
             .unwrap();
 
-        let start = addresses.start as usize;
-        let end = addresses.end as usize;
-        let bank_size = end - start;
+        let offset = (addr - addresses.start) as usize;
 
-        let offset = if self.prg_rom_mode == PrgRomMode::Switch32kB {
-            addr % 0x8000
-        } else {
-            addr % 0x4000
-        } as usize;
+        self.prg_rom
+            .chunks_exact(0x4000)
+            .nth(*bank % self.num_banks)
+            .unwrap()[offset]
+    }
+
+    /// Where in the PRG RAM an access to $6000-$7FFF goes, or `None` if the RAM is disabled or
+    /// missing
+    fn prg_ram_offset(&self, addr: u16) -> Option<usize> {
+        if self.bus.prg_ram.is_empty() || !self.ram_enabled() {
+            return None;
+        }
+
+        let offset = self.prg_ram_bank() * 0x2000 + (addr - 0x6000) as usize;
 
-        self.prg_rom.chunks_exact(bank_size).nth(*bank).unwrap()[offset]
+        Some(offset % self.bus.prg_ram.len())
     }
 
-    fn write(&mut self, addr: u16, data: u8) {
-        match addr {
-            0x8000..=0x9FFF => {
-                let mirroring = data.bits_abs(0, 1);
+    // Boards with CHR RAM repurpose the upper bits of the CHR registers. The real chip uses
+    // whichever register the PPU last fetched through in 4KiB mode, but games write the same
+    // value to both, so the first one stands in for them.
+    fn board_bits(&self) -> u8 {
+        self.chr_banks[0]
+    }
 
-                let prg_rom_mode = data.bits(2, 3);
+    /// SUROM and SXROM pick the 256KiB half of PRG ROM with bit 4 of the CHR register
+    fn outer_prg_bank(&self) -> usize {
+        if self.prg_rom.len() > OUTER_PRG_BANK_SIZE {
+            self.board_bits().bit(4) as usize * (OUTER_PRG_BANK_SIZE / 0x4000)
+        } else {
+            0
+        }
+    }
 
-                let chr_rom_mode = data.bit(4);
+    /// SOROM selects one of two 8KiB PRG RAM banks with bit 3 of the CHR register, SXROM one of
+    /// four with bits 2-3
+    fn prg_ram_bank(&self) -> usize {
+        match self.bus.prg_ram.len() {
+            0x4000 => self.board_bits().bit(3) as usize,
+            0x8000 => self.board_bits().bits(2, 3) as usize,
+            _ => 0,
+        }
+    }
 
-                let mirroring = match mirroring {
-                    0 => Mirroring::OneScreenLowerBank,
-                    1 => Mirroring::OneScreenUpperBank,
-                    2 => Mirroring::Vertical,
-                    3 => Mirroring::Horizontal,
-                    _ => unreachable!(),
-                };
+    fn ram_enabled(&self) -> bool {
+        // SNROM wires bit 4 of the CHR register to a second enable on the RAM
+        let snrom = self.bus.ppu.ram
+            && self.bus.prg_ram.len() == 0x2000
+            && self.prg_rom.len() <= OUTER_PRG_BANK_SIZE;
 
-                self.bus.ppu.set_mirroring_mode(mirroring);
+        if snrom && self.board_bits().bit(4) {
+            return false;
+        }
 
-                self.prg_rom_mode = match prg_rom_mode {
-                    0 | 1 => {
-                        self.bank_settings
-                            .replace(BankSettings::new(vec![(self.cur_bank, (0x8000..0x10000))]));
-                        PrgRomMode::Switch32kB
-                    }
-                    2 => {
-                        self.bank_settings.replace(BankSettings::new(vec![
-                            (0, (0x8000..0xC000)),
-                            (self.cur_bank, (0xC000..0x10000)),
-                        ]));
-                        PrgRomMode::FixFirstBank
-                    }
-                    3 => {
-                        self.bank_settings.replace(BankSettings::new(vec![
-                            (self.cur_bank, (0x8000..0xC000)),
-                            (self.num_banks - 1, (0xC000..0x10000)),
-                        ]));
-                        PrgRomMode::FixLastBank
-                    }
-                    _ => unreachable!(),
-                };
+        self.mmc1a || !self.prg_bank.bit(4)
+    }
 
-                self.chr_rom_mode = if chr_rom_mode {
-                    self.ppu().bank_settings.replace(BankSettings::new(vec![
-                        (0, (0..0x1000)),
-                        (1, (0x1000..0x2000)),
-                    ]));
-                    ChrRomMode::SwitchTwo4kB
-                } else {
-                    self.ppu()
-                        .bank_settings
-                        .replace(BankSettings::new(vec![(0, 0x0000..0x2000)]));
-                    ChrRomMode::Switch8kB
-                };
-            }
-            0xA000..=0xBFFF => {
-                let prg_ram_disable = data.bit(4);
+    /// Recomputes the PRG and CHR banks from the registers
+    fn update_banks(&mut self) {
+        let outer = self.outer_prg_bank();
+        let inner_banks = self.num_banks.min(OUTER_PRG_BANK_SIZE / 0x4000);
+        let bank = |bank: usize| outer + bank % inner_banks;
 
-                if !prg_ram_disable {
-                    self.ram_enabled = true;
-                };
+        let prg_bank = self.prg_bank.bits(0, 3) as usize;
 
-                if matches!(self.chr_rom_mode, ChrRomMode::SwitchTwo4kB) {
-                    // TODO non-SNROM
-                    let data = data.bits_abs(0, 4) as usize;
+        let (first, second) = match self.control.bits(2, 3) {
+            0 | 1 => (bank(prg_bank & !1), bank(prg_bank | 1)),
+            2 => (bank(0), bank(prg_bank)),
+            3 => (bank(prg_bank), bank(inner_banks - 1)),
+            _ => unreachable!(),
+        };
 
-                    self.ppu().bank_settings.set_bank(data, 0x0000..0x1000);
-                } else {
-                    let data = data.bits_abs(1, 4) as usize;
+        self.bank_settings.replace(BankSettings::new(vec![
+            (first, (0x8000..0xC000)),
+            (second, (0xC000..0x10000)),
+        ]));
 
-                    self.ppu().bank_settings.set_bank(data, 0x0000..0x2000);
-                }
-            }
-            0xC000..=0xDFFF => {
-                if matches!(self.chr_rom_mode, ChrRomMode::SwitchTwo4kB) {
-                    // TODO non-SNROM
-                    let data = data.bits_abs(0, 4) as usize;
+        let chr_banks = self.chr_banks.map(|bank| bank.bits(0, 4) as usize);
+        let chr_len = self.bus.ppu.chr.len();
 
-                    self.ppu().bank_settings.set_bank(data, 0x1000..0x2000);
-                }
-            }
-            0xE000..=0xFFFF => {
-                // TODO MMC1A bypass
-                let prg_ram_disable = data.bit(4);
+        let settings = if self.control.bit(4) {
+            let banks = (chr_len / 0x1000).max(1);
 
-                self.ram_enabled = !prg_ram_disable;
+            vec![
+                (chr_banks[0] % banks, (0x0000..0x1000)),
+                (chr_banks[1] % banks, (0x1000..0x2000)),
+            ]
+        } else {
+            let banks = (chr_len / 0x2000).max(1);
 
-                match self.prg_rom_mode {
-                    PrgRomMode::Switch32kB => {
-                        let data = data.bits(1, 3) as usize;
+            vec![((chr_banks[0] >> 1) % banks, (0x0000..0x2000))]
+        };
 
-                        self.cur_bank = data;
-                        self.bank_settings.set_bank(data, 0x8000..0x10000);
-                    }
-                    PrgRomMode::FixFirstBank => {
-                        let data = data.bits(0, 3) as usize;
+        self.ppu()
+            .bank_settings
+            .replace(BankSettings::new(settings));
+    }
 
-                        self.cur_bank = data;
-                        self.bank_settings.set_bank(data, 0xC000..0x10000);
-                    }
-                    PrgRomMode::FixLastBank => {
-                        let data = data.bits(0, 3) as usize;
+    fn write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x8000..=0x9FFF => {
+                let mirroring = match data.bits(0, 1) {
+                    0 => Mirroring::OneScreenLowerBank,
+                    1 => Mirroring::OneScreenUpperBank,
+                    2 => Mirroring::Vertical,
+                    3 => Mirroring::Horizontal,
+                    _ => unreachable!(),
+                };
 
-                        self.cur_bank = data;
-                        self.bank_settings.set_bank(data, 0x8000..0xC000);
-                    }
-                }
+                self.bus.ppu.set_mirroring_mode(mirroring);
+
+                self.control = data;
             }
+            0xA000..=0xBFFF => self.chr_banks[0] = data,
+            0xC000..=0xDFFF => self.chr_banks[1] = data,
+            0xE000..=0xFFFF => self.prg_bank = data,
             _ => unreachable!(),
         }
+
+        self.update_banks();
     }
 }
 
@@ -206,6 +218,7 @@ impl MapperTrait for Mapper1 {
         &mut self.bus.apu
     }
 
+    #[cfg(feature = "sdl")]
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -214,8 +227,8 @@ impl MapperTrait for Mapper1 {
         &mut self.bus
     }
 
-    fn controller(&mut self) -> &mut Controller {
-        &mut self.bus.controller1
+    fn controller(&mut self, port: usize) -> &mut Controller {
+        self.bus.controller(port)
     }
 
     fn memory(&self) -> &[u8] {
@@ -226,48 +239,56 @@ impl MapperTrait for Mapper1 {
         &mut self.bus.ppu
     }
 
+    fn ppu_ref(&self) -> &Ppu {
+        &self.bus.ppu
+    }
+
     fn read_u8(&self, addr: u16) -> u8 {
         match addr {
-            0x6000..=0x7FFF => {
-                if self.ram_enabled {
-                    let offset = addr as usize % 0x6000;
-
-                    self.sram[offset]
-                } else {
-                    0
-                }
-            }
+            0x6000..=0x7FFF => match self.prg_ram_offset(addr) {
+                Some(offset) => self.bus.prg_ram[offset],
+                None => 0,
+            },
             0x8000..=0xFFFF => self.read_prg(addr),
             _ => self.bus.read_u8(addr),
         }
     }
 
+    fn restore(&mut self) {
+        if !self.sram.is_empty() {
+            self.bus.prg_ram = std::mem::take(&mut self.sram);
+        }
+    }
+
     fn save_data(&mut self) {
-        save_ram(&self.filename, self.sram.as_slice());
+        self.bus.save_battery_ram(&self.filename);
     }
 
     fn write_u8(&mut self, addr: u16, data: u8) {
         match addr {
             0x6000..=0x7FFF => {
-                if self.ram_enabled {
-                    let offset = addr as usize % 0x6000;
-
-                    self.sram[offset] = data;
+                if let Some(offset) = self.prg_ram_offset(addr) {
+                    self.bus.prg_ram[offset] = data;
                 }
             }
-            // TODO consecutive-cycle writes
             0x8000..=0xFFFF => {
+                let cycle = self.bus.cycle;
+                let last_write = self.last_write.replace(cycle);
+
+                // The serial port ignores a write on the cycle right after another one, like the
+                // second write of a read-modify-write instruction
+                if last_write.is_some_and(|last| last + 1 == cycle) {
+                    return;
+                }
+
                 let bit7 = data.bit(7);
 
                 if bit7 {
-                    self.prg_rom_mode = PrgRomMode::FixLastBank;
-                    self.bank_settings.replace(BankSettings::new(vec![
-                        (self.cur_bank, (0x8000..0xC000)),
-                        (self.num_banks - 1, (0xC000..0x10000)),
-                    ]));
-
                     self.shift_reg.clear();
                     self.writes = 0;
+
+                    self.control |= 0x0C;
+                    self.update_banks();
                 } else if self.writes < 4 {
                     let data_bit = data.bit(0) as u8;
 
@@ -304,11 +325,22 @@ impl DebugInfo for Mapper1 {
                 })
             });
 
+            ui.label(format!(
+                "PRG RAM size: {}K (bank {}, {})",
+                self.bus.prg_ram.len() / 1024,
+                self.prg_ram_bank(),
+                if self.ram_enabled() {
+                    "enabled"
+                } else {
+                    "disabled"
+                }
+            ));
+
             ui.horizontal(|ui| {
                 ui.label(format!(
                     "CHR size: {}K ({})",
                     self.bus.ppu.chr.len() / 1024,
-                    if self.ram_enabled { "RAM" } else { "ROM" }
+                    if self.bus.ppu.ram { "RAM" } else { "ROM" }
                 ));
                 ui.vertical(|ui| {
                     for (bank, addresses) in self.bus.ppu.bank_settings.iter() {
@@ -319,7 +351,11 @@ impl DebugInfo for Mapper1 {
                     }
                 })
             });
-            ui.label(format!("Mirroring: {:?}", self.bus.ppu.mirroring));
+            ui.label(format!(
+                "Mirroring: {:?} ({})",
+                self.bus.ppu.mirroring,
+                if self.mmc1a { "MMC1A" } else { "MMC1B" }
+            ));
         });
     }
 }
@@ -1,6 +1,7 @@
 use crate::bus::Bus;
 use crate::cartridge::MapperType;
 use crate::controller::Controller;
+use crate::region::Region;
 
 use super::*;
 
@@ -14,7 +15,7 @@ pub struct Mapper2 {
 }
 
 impl Mapper2 {
-    pub fn new(mut cartridge: Cartridge, clockrate: u32) -> Self {
+    pub fn new(mut cartridge: Cartridge, region: Region) -> Self {
         let prg_rom = cartridge.prg_rom.take().unwrap();
 
         let num_banks = prg_rom.len() / 0x4000;
@@ -24,7 +25,7 @@ impl Mapper2 {
                 (0, (0x8000..0xC000)),
                 (num_banks - 1, (0xC000..0x10000)),
             ]),
-            bus: Bus::new(cartridge, MapperType::Uxrom, clockrate),
+            bus: Bus::new(cartridge, MapperType::Uxrom, region),
             cur_bank: 0,
             num_banks,
             prg_rom,
@@ -37,6 +38,7 @@ impl MapperTrait for Mapper2 {
         &mut self.bus.apu
     }
 
+    #[cfg(feature = "sdl")]
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -45,8 +47,8 @@ impl MapperTrait for Mapper2 {
         &mut self.bus
     }
 
-    fn controller(&mut self) -> &mut Controller {
-        &mut self.bus.controller1
+    fn controller(&mut self, port: usize) -> &mut Controller {
+        self.bus.controller(port)
     }
 
     fn memory(&self) -> &[u8] {
@@ -57,6 +59,10 @@ impl MapperTrait for Mapper2 {
         &mut self.bus.ppu
     }
 
+    fn ppu_ref(&self) -> &Ppu {
+        &self.bus.ppu
+    }
+
     fn read_u8(&self, addr: u16) -> u8 {
         match addr {
             0x8000..=0xBFFF => {
@@ -1,12 +1,13 @@
 use egui::Ui;
-use serde_big_array::BigArray;
 
+use crate::bus::irq::IrqSource;
 use crate::bus::Bus;
 use crate::cartridge::{MapperType, Mirroring};
 use crate::controller::Controller;
 use crate::gui::DebugInfo;
+use crate::region::Region;
 use crate::util::bit::Bit;
-use crate::util::{load_ram, save_ram, Config};
+use crate::util::Config;
 
 use super::*;
 
@@ -35,19 +36,26 @@ pub struct Mapper4 {
     ram_enabled: bool,
     ram_protected: bool,
     selected_bank_register: usize,
-    #[serde(with = "BigArray")]
-    sram: [u8; 0x2000],
+    // PRG RAM of save states from before it moved to the bus
+    #[serde(default, skip_serializing)]
+    sram: Vec<u8>,
 }
 
 impl Mapper4 {
-    pub fn new(mut cartridge: Cartridge, config: &Config, clockrate: u32) -> Self {
+    pub fn new(mut cartridge: Cartridge, config: &Config, region: Region) -> Self {
         let prg_rom = cartridge.prg_rom.take().unwrap();
 
+        let mut bus = Bus::new(cartridge, MapperType::MMC3, region);
+
+        if !config.no_battery {
+            bus.load_battery_ram(&config.filename);
+        }
+
         let num_banks = prg_rom.len() / 0x2000;
 
         Self {
             prg_rom,
-            bus: Bus::new(cartridge, MapperType::MMC3, clockrate),
+            bus,
             prg_rom_mode: PrgRomMode::SwappableFixed,
             banks: [0, 0, 0, 0, 0, 0, 0, 0, num_banks - 2, num_banks - 1],
             bank_settings: BankSettings::new(vec![
@@ -57,12 +65,12 @@ impl Mapper4 {
                 (9, (0xE000..0x10000)),
             ]),
             chr_rom_mode: ChrRomMode::TwoFour,
-            sram: load_ram(&config.filename),
             selected_bank_register: 0,
             ram_enabled: true,
             ram_protected: false,
             filename: config.filename.clone(),
             num_banks,
+            sram: Vec::new(),
         }
     }
 
@@ -92,6 +100,7 @@ impl MapperTrait for Mapper4 {
         &mut self.bus.apu
     }
 
+    #[cfg(feature = "sdl")]
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -100,8 +109,8 @@ impl MapperTrait for Mapper4 {
         &mut self.bus
     }
 
-    fn controller(&mut self) -> &mut Controller {
-        &mut self.bus.controller1
+    fn controller(&mut self, port: usize) -> &mut Controller {
+        self.bus.controller(port)
     }
 
     fn memory(&self) -> &[u8] {
@@ -112,13 +121,15 @@ impl MapperTrait for Mapper4 {
         &mut self.bus.ppu
     }
 
+    fn ppu_ref(&self) -> &Ppu {
+        &self.bus.ppu
+    }
+
     fn read_u8(&self, addr: u16) -> u8 {
         match addr {
             0x6000..=0x7FFF => {
                 if self.ram_enabled {
-                    let offset = addr as usize % 0x6000;
-
-                    self.sram[offset]
+                    self.bus.read_u8(addr)
                 } else {
                     0
                 }
@@ -128,8 +139,14 @@ impl MapperTrait for Mapper4 {
         }
     }
 
+    fn restore(&mut self) {
+        if !self.sram.is_empty() {
+            self.bus.prg_ram = std::mem::take(&mut self.sram);
+        }
+    }
+
     fn save_data(&mut self) {
-        save_ram(&self.filename, self.sram.as_slice());
+        self.bus.save_battery_ram(&self.filename);
     }
 
     fn write_u8(&mut self, addr: u16, data: u8) {
@@ -139,9 +156,7 @@ impl MapperTrait for Mapper4 {
             0x6000..=0x7FFF => {
                 // TODO MMC6
                 if self.ram_enabled && !self.ram_protected {
-                    let offset = addr as usize % 0x6000;
-
-                    self.sram[offset] = data;
+                    self.bus.write_u8(addr, data);
                 }
             }
             0x8000..=0x9FFE if addr % 2 == 0 => {
@@ -225,8 +240,9 @@ impl MapperTrait for Mapper4 {
                 mmc3.irq.counter = 0;
             }
             0xE000..=0xFFFE if addr % 2 == 0 => {
-                mmc3.irq.occurred = false;
-                mmc3.irq.enabled = false
+                mmc3.irq.enabled = false;
+
+                self.bus.irq.acknowledge(IrqSource::Mapper);
             }
             0xE001..=0xFFFF => mmc3.irq.enabled = true,
             _ => self.bus.write_u8(addr, data),
@@ -1,6 +1,7 @@
 use crate::bus::Bus;
 use crate::cartridge::MapperType;
 use crate::controller::Controller;
+use crate::region::Region;
 use crate::util::bit::Bit;
 
 use super::*;
@@ -15,13 +16,13 @@ pub struct Mapper7 {
 }
 
 impl Mapper7 {
-    pub fn new(mut cartridge: Cartridge, clockrate: u32) -> Self {
+    pub fn new(mut cartridge: Cartridge, region: Region) -> Self {
         let prg_rom = cartridge.prg_rom.take().unwrap();
 
-        let num_banks = prg_rom.len() / 0x4000;
+        let num_banks = prg_rom.len() / 0x8000;
 
         Self {
-            bus: Bus::new(cartridge, MapperType::Axrom, clockrate),
+            bus: Bus::new(cartridge, MapperType::Axrom, region),
             cur_bank: 0,
             num_banks,
             prg_rom,
@@ -35,6 +36,7 @@ impl MapperTrait for Mapper7 {
         &mut self.bus.apu
     }
 
+    #[cfg(feature = "sdl")]
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -43,18 +45,30 @@ impl MapperTrait for Mapper7 {
         &mut self.bus
     }
 
-    fn controller(&mut self) -> &mut Controller {
-        &mut self.bus.controller1
+    fn controller(&mut self, port: usize) -> &mut Controller {
+        self.bus.controller(port)
     }
 
     fn memory(&self) -> &[u8] {
         &self.bus.ram
     }
 
+    fn nametables(&self) -> [Nametable; 4] {
+        if self.vram_page == 0 {
+            [Nametable::CiramA; 4]
+        } else {
+            [Nametable::CiramB; 4]
+        }
+    }
+
     fn ppu(&mut self) -> &mut Ppu {
         &mut self.bus.ppu
     }
 
+    fn ppu_ref(&self) -> &Ppu {
+        &self.bus.ppu
+    }
+
     fn read_u8(&self, addr: u16) -> u8 {
         match addr {
             0x8000..=0xFFFF => {
@@ -62,7 +76,7 @@ impl MapperTrait for Mapper7 {
 
                 self.prg_rom
                     .chunks_exact(0x8000)
-                    .nth(self.cur_bank)
+                    .nth(self.cur_bank % self.num_banks)
                     .unwrap()[addr]
             }
             _ => self.bus.read_u8(addr),
@@ -78,7 +92,10 @@ impl MapperTrait for Mapper7 {
                 let vram_page = data.bit(4) as u8;
 
                 self.cur_bank = bank;
-                self.bus.ppu.mmc7_vram_page = vram_page;
+                self.vram_page = vram_page;
+
+                let slots = self.nametables();
+                self.map_nametables(slots);
             }
             _ => self.bus.write_u8(addr, data),
         }
@@ -102,8 +119,33 @@ impl DebugInfo for Mapper7 {
             ));
             ui.label(format!(
                 "Mirroring: Single-screen (VRAM page #{})",
-                self.bus.ppu.mmc7_vram_page
+                self.vram_page
             ));
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bank_wraps_to_rom_size() {
+        // Mapper 7 with 64KiB of PRG ROM and CHR RAM, each 32KiB bank starting with its number
+        let mut rom = b"NES\x1A\x04\x00\x70\x00".to_vec();
+        rom.resize(0x10, 0);
+
+        for bank in 0..2 {
+            let mut prg = vec![0; 0x8000];
+            prg[0] = bank;
+            rom.extend(prg);
+        }
+
+        let cartridge = Cartridge::from_bytes(&rom).unwrap();
+        let mut mapper = Mapper7::new(cartridge, Region::Ntsc);
+
+        mapper.write_u8(0x8000, 7);
+
+        assert_eq!(mapper.read_u8(0x8000), 1);
+    }
+}
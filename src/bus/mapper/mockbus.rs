@@ -6,6 +6,7 @@ use crate::bus::Bus;
 use crate::cartridge::MapperType;
 use crate::controller::Controller;
 use crate::gui::DebugInfo;
+use crate::region::Region;
 
 use super::*;
 
@@ -17,9 +18,9 @@ pub struct MockBus {
 }
 
 impl MockBus {
-    pub fn new(cartridge: Cartridge, clockrate: u32) -> Self {
+    pub fn new(cartridge: Cartridge, region: Region) -> Self {
         Self {
-            bus: Bus::new(cartridge, MapperType::Nrom, clockrate),
+            bus: Bus::new(cartridge, MapperType::Nrom, region),
             ram: [0; 0x10000],
         }
     }
@@ -30,6 +31,7 @@ impl MapperTrait for MockBus {
         &mut self.bus.apu
     }
 
+    #[cfg(feature = "sdl")]
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -38,8 +40,8 @@ impl MapperTrait for MockBus {
         &mut self.bus
     }
 
-    fn controller(&mut self) -> &mut Controller {
-        &mut self.bus.controller1
+    fn controller(&mut self, port: usize) -> &mut Controller {
+        self.bus.controller(port)
     }
 
     fn memory(&self) -> &[u8] {
@@ -50,6 +52,10 @@ impl MapperTrait for MockBus {
         &mut self.bus.ppu
     }
 
+    fn ppu_ref(&self) -> &Ppu {
+        &self.bus.ppu
+    }
+
     fn read_u8(&self, addr: u16) -> u8 {
         self.ram[addr as usize]
     }
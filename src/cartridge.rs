@@ -1,5 +1,6 @@
 use std::error::Error;
-use std::fs;
+use std::fmt::{Display, Formatter};
+use std::{fs, io};
 
 use serde::{Deserialize, Serialize};
 
@@ -15,12 +16,75 @@ struct INESHeader {
     flags8: u8,
     flags9: u8,
     flags10: u8,
-    unused: [u8; 5],
+    flags11: u8,
+    flags12: u8,
+    flags13: u8,
+    flags14: u8,
+    flags15: u8,
 }
 
-fn parse_ines_header(header_bytes: &[u8]) -> Result<INESHeader, Box<dyn Error>> {
+#[derive(Debug)]
+pub enum CartridgeError {
+    BadMagic,
+    Io(io::Error),
+    InvalidPrgRomSize(MapperType, usize),
+    TruncatedChrRom { expected: usize, actual: usize },
+    TruncatedHeader { expected: usize, actual: usize },
+    TruncatedPrgRom { expected: usize, actual: usize },
+    UnsupportedFeature(&'static str),
+    UnsupportedMapper(u16),
+}
+
+impl Display for CartridgeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CartridgeError::BadMagic => write!(f, "Not an INES file"),
+            CartridgeError::Io(error) => write!(f, "Unable to read ROM: {error}"),
+            CartridgeError::InvalidPrgRomSize(mapper_type, size) => write!(
+                f,
+                "PRG ROM size of {}K is not valid for {mapper_type:?}",
+                size / 1024
+            ),
+            CartridgeError::TruncatedChrRom { expected, actual } => write!(
+                f,
+                "CHR ROM is truncated: expected {expected} bytes, found {actual}"
+            ),
+            CartridgeError::TruncatedHeader { expected, actual } => write!(
+                f,
+                "Header is truncated: expected {expected} bytes, found {actual}"
+            ),
+            CartridgeError::TruncatedPrgRom { expected, actual } => write!(
+                f,
+                "PRG ROM is truncated: expected {expected} bytes, found {actual}"
+            ),
+            CartridgeError::UnsupportedFeature(feature) => {
+                write!(f, "{feature} not supported")
+            }
+            CartridgeError::UnsupportedMapper(mapper_num) => {
+                write!(f, "Mapper {mapper_num} not implemented yet")
+            }
+        }
+    }
+}
+
+impl Error for CartridgeError {}
+
+impl From<io::Error> for CartridgeError {
+    fn from(error: io::Error) -> Self {
+        CartridgeError::Io(error)
+    }
+}
+
+fn parse_ines_header(header_bytes: &[u8]) -> Result<INESHeader, CartridgeError> {
+    if header_bytes.len() < 0x10 {
+        return Err(CartridgeError::TruncatedHeader {
+            expected: 0x10,
+            actual: header_bytes.len(),
+        });
+    }
+
     let header = INESHeader {
-        magic: header_bytes[0..4].try_into()?,
+        magic: header_bytes[0..4].try_into().unwrap(),
         prg_rom_size: header_bytes[4],
         chr_rom_size: header_bytes[5],
         flags6: header_bytes[6],
@@ -28,16 +92,39 @@ fn parse_ines_header(header_bytes: &[u8]) -> Result<INESHeader, Box<dyn Error>>
         flags8: header_bytes[8],
         flags9: header_bytes[9],
         flags10: header_bytes[10],
-        unused: [0; 5],
+        flags11: header_bytes[11],
+        flags12: header_bytes[12],
+        flags13: header_bytes[13],
+        flags14: header_bytes[14],
+        flags15: header_bytes[15],
     };
 
     if header.magic != [b'N', b'E', b'S', 0x1A] {
-        return Err("Not an INES file".into());
+        return Err(CartridgeError::BadMagic);
     }
 
     Ok(header)
 }
 
+fn nes2_rom_size(lsb: u8, msb: u8, unit: usize) -> usize {
+    if msb == 0xF {
+        let exponent = lsb.bits(2, 7) as u32;
+        let multiplier = lsb.bits_abs(0, 1) as usize * 2 + 1;
+
+        (1usize << exponent).saturating_mul(multiplier)
+    } else {
+        ((msb as usize) << 8 | lsb as usize) * unit
+    }
+}
+
+fn nes2_ram_size(shift_count: u8) -> usize {
+    if shift_count == 0 {
+        0
+    } else {
+        64 << shift_count
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
 pub enum MapperType {
     #[default]
@@ -49,12 +136,53 @@ pub enum MapperType {
     Axrom,
 }
 
+impl MapperType {
+    fn valid_prg_rom_size(&self, size: usize) -> bool {
+        match self {
+            MapperType::Nrom | MapperType::Cnrom => size == 0x4000 || size == 0x8000,
+            MapperType::MMC1 | MapperType::Uxrom => size > 0 && size.is_multiple_of(0x4000),
+            MapperType::MMC3 => size >= 0x4000 && size.is_multiple_of(0x2000),
+            MapperType::Axrom => size > 0 && size.is_multiple_of(0x8000),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum Timing {
+    #[default]
+    Ntsc,
+    Pal,
+    MultiRegion,
+    Dendy,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum ConsoleType {
+    #[default]
+    Nes,
+    VsSystem,
+    Playchoice10,
+    Extended(u8),
+}
+
 #[derive(Clone)]
 pub struct Cartridge {
+    pub battery: bool,
+    pub chr_nvram_size: usize,
+    pub chr_ram_size: usize,
     pub chr_rom: Option<Vec<u8>>,
+    pub console_type: ConsoleType,
+    pub expansion_device: u8,
+    pub mapper_num: u16,
     pub mapper_type: MapperType,
     pub mirroring: Mirroring,
+    pub nes2: bool,
+    pub prg_nvram_size: usize,
+    pub prg_ram_size: usize,
     pub prg_rom: Option<Vec<u8>>,
+    pub submapper: u8,
+    pub timing: Timing,
+    pub trainer: Option<Vec<u8>>,
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
@@ -68,32 +196,103 @@ pub enum Mirroring {
 }
 
 impl Cartridge {
-    pub fn new(filename: &str) -> Result<Self, Box<dyn Error>> {
-        let file = fs::read(filename)?;
+    pub fn new(filename: &str) -> Result<Self, CartridgeError> {
+        Self::from_bytes(&fs::read(filename)?)
+    }
 
-        let header = parse_ines_header(&file[..0x10])?;
+    /// Parses the contents of an iNES or NES 2.0 file
+    pub fn from_bytes(file: &[u8]) -> Result<Self, CartridgeError> {
+        let header = parse_ines_header(file)?;
 
-        let prg_rom_size = header.prg_rom_size as usize * 0x400 * 16;
-        let chr_rom_size = header.chr_rom_size as usize * 0x400 * 8;
-        let prg_ram_size = header.flags8 as usize * 0x400 * 8;
+        let battery = header.flags6.bit(1);
+        let has_trainer = header.flags6.bit(2);
 
-        if prg_ram_size > 1 {
-            todo!("PRG RAM size > 8KiB not supported.");
-        }
+        let nes2 = header.flags7.bits(2, 3) == 2;
 
-        if header.flags6.bit(2) {
-            todo!("Trainers are not supported.");
-        }
+        let mapper_ln = header.flags6.bits(4, 7) as u16;
+        let mapper_un = header.flags7.bits_abs(4, 7) as u16;
 
-        let ines_ver = header.flags7.bits(2, 3);
+        let (mapper_num, submapper) = if nes2 {
+            let mapper_hn = (header.flags8.bits_abs(0, 3) as u16) << 8;
 
-        if ines_ver == 2 {
-            todo!("INES 2.0 not supported.");
-        }
+            (mapper_hn | mapper_un | mapper_ln, header.flags8.bits(4, 7))
+        } else {
+            (mapper_un | mapper_ln, 0)
+        };
+
+        let prg_rom_size;
+        let chr_rom_size;
+        let prg_ram_size;
+        let prg_nvram_size;
+        let chr_ram_size;
+        let chr_nvram_size;
+        let console_type;
+        let timing;
+        let expansion_device;
 
-        let mapper_ln = header.flags6.bits(4, 7);
-        let mapper_un = header.flags7.bits_abs(4, 7);
-        let mapper_num = mapper_ln | mapper_un;
+        if nes2 {
+            prg_rom_size = nes2_rom_size(header.prg_rom_size, header.flags9.bits(0, 3), 0x4000);
+            chr_rom_size = nes2_rom_size(header.chr_rom_size, header.flags9.bits(4, 7), 0x2000);
+            prg_ram_size = nes2_ram_size(header.flags10.bits(0, 3));
+            prg_nvram_size = nes2_ram_size(header.flags10.bits(4, 7));
+            chr_ram_size = nes2_ram_size(header.flags11.bits(0, 3));
+            chr_nvram_size = nes2_ram_size(header.flags11.bits(4, 7));
+
+            console_type = match header.flags7.bits(0, 1) {
+                0 => ConsoleType::Nes,
+                1 => ConsoleType::VsSystem,
+                2 => ConsoleType::Playchoice10,
+                3 => ConsoleType::Extended(header.flags13.bits(0, 3)),
+                _ => unreachable!(),
+            };
+
+            match console_type {
+                ConsoleType::Nes => {}
+                ConsoleType::VsSystem => {
+                    return Err(CartridgeError::UnsupportedFeature("Vs. System"))
+                }
+                ConsoleType::Playchoice10 => {
+                    return Err(CartridgeError::UnsupportedFeature("PlayChoice-10"))
+                }
+                ConsoleType::Extended(_) => {
+                    return Err(CartridgeError::UnsupportedFeature("Extended console type"))
+                }
+            }
+
+            timing = match header.flags12.bits(0, 1) {
+                0 => Timing::Ntsc,
+                1 => Timing::Pal,
+                2 => Timing::MultiRegion,
+                3 => Timing::Dendy,
+                _ => unreachable!(),
+            };
+
+            expansion_device = header.flags15.bits(0, 5);
+        } else {
+            prg_rom_size = header.prg_rom_size as usize * 0x400 * 16;
+            chr_rom_size = header.chr_rom_size as usize * 0x400 * 8;
+
+            // A value of 0 infers 8KiB for compatibility
+            let ram_size = header.flags8.max(1) as usize * 0x400 * 8;
+
+            if battery {
+                prg_ram_size = 0;
+                prg_nvram_size = ram_size;
+            } else {
+                prg_ram_size = ram_size;
+                prg_nvram_size = 0;
+            }
+
+            chr_ram_size = if chr_rom_size == 0 { 0x2000 } else { 0 };
+            chr_nvram_size = 0;
+            console_type = ConsoleType::Nes;
+            timing = if header.flags9.bit(0) {
+                Timing::Pal
+            } else {
+                Timing::Ntsc
+            };
+            expansion_device = 0;
+        }
 
         let mirroring = if header.flags6.bit(3) {
             Mirroring::FourScreen
@@ -105,12 +304,6 @@ impl Cartridge {
             Mirroring::Horizontal
         };
 
-        let start_of_chr_rom = 0x10 + prg_rom_size;
-
-        let chr_rom = Vec::from(&file[start_of_chr_rom..start_of_chr_rom + chr_rom_size]);
-
-        let prg_rom = Vec::from(&file[0x10..0x10 + prg_rom_size]);
-
         let mapper_type = match mapper_num {
             0 => MapperType::Nrom,
             1 => MapperType::MMC1,
@@ -118,16 +311,93 @@ impl Cartridge {
             3 => MapperType::Cnrom,
             4 => MapperType::MMC3,
             7 => MapperType::Axrom,
-            _ => {
-                todo!("Mapper {mapper_num} not implemented yet")
-            }
+            _ => return Err(CartridgeError::UnsupportedMapper(mapper_num)),
         };
 
+        if !mapper_type.valid_prg_rom_size(prg_rom_size) {
+            return Err(CartridgeError::InvalidPrgRomSize(mapper_type, prg_rom_size));
+        }
+
+        let start_of_prg_rom: usize = if has_trainer { 0x210 } else { 0x10 };
+        let end_of_prg_rom = start_of_prg_rom.saturating_add(prg_rom_size);
+        let end_of_chr_rom = end_of_prg_rom.saturating_add(chr_rom_size);
+
+        if file.len() < end_of_prg_rom {
+            return Err(CartridgeError::TruncatedPrgRom {
+                expected: prg_rom_size,
+                actual: file.len().saturating_sub(start_of_prg_rom),
+            });
+        }
+
+        if file.len() < end_of_chr_rom {
+            return Err(CartridgeError::TruncatedChrRom {
+                expected: chr_rom_size,
+                actual: file.len() - end_of_prg_rom,
+            });
+        }
+
+        let trainer = if has_trainer {
+            Some(Vec::from(&file[0x10..0x210]))
+        } else {
+            None
+        };
+
+        let chr_rom = Vec::from(&file[end_of_prg_rom..end_of_chr_rom]);
+
+        let prg_rom = Vec::from(&file[start_of_prg_rom..end_of_prg_rom]);
+
         Ok(Self {
+            battery,
+            chr_nvram_size,
+            chr_ram_size,
             chr_rom: Some(chr_rom),
+            console_type,
+            expansion_device,
+            mapper_num,
             mapper_type,
             mirroring,
+            nes2,
+            prg_nvram_size,
+            prg_ram_size,
             prg_rom: Some(prg_rom),
+            submapper,
+            timing,
+            trainer,
         })
     }
+
+    pub fn total_prg_ram_size(&self) -> usize {
+        self.prg_ram_size + self.prg_nvram_size
+    }
+
+    pub fn total_chr_ram_size(&self) -> usize {
+        self.chr_ram_size + self.chr_nvram_size
+    }
+
+    /// Nametable RAM the board adds to the console's 2KiB. Four-screen boards carry another
+    /// 2KiB so all four nametables can hold something different.
+    pub fn nametable_ram_size(&self) -> usize {
+        match self.mirroring {
+            Mirroring::FourScreen => 0x800,
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_file_is_a_truncated_header() {
+        let result = Cartridge::from_bytes(b"NES\x1A\x01\x01\x00\x00\x00\x00");
+
+        assert!(matches!(
+            result,
+            Err(CartridgeError::TruncatedHeader {
+                expected: 16,
+                actual: 10
+            })
+        ));
+    }
 }
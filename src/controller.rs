@@ -2,6 +2,7 @@ use std::cell::Cell;
 
 use serde::{Deserialize, Serialize};
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
 #[repr(usize)]
 pub enum Btn {
     A,
@@ -14,6 +15,33 @@ pub enum Btn {
     Right,
 }
 
+/// Button state of a standard controller for one frame, indexed by `Btn`
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq)]
+pub struct Input {
+    pub buttons: [bool; 8],
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn press(&mut self, button: Btn) {
+        self.buttons[button as usize] = true;
+    }
+
+    pub fn release(&mut self, button: Btn) {
+        self.buttons[button as usize] = false;
+    }
+
+    /// Also holds the buttons held in `other`
+    pub fn combine(&mut self, other: Input) {
+        for (held, other) in self.buttons.iter_mut().zip(other.buttons) {
+            *held |= other;
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub struct Controller {
     buttons: [bool; 8],
@@ -48,6 +76,10 @@ impl Controller {
         self.buttons[button as usize] = false;
     }
 
+    pub fn set_input(&mut self, input: Input) {
+        self.buttons = input.buttons;
+    }
+
     pub fn write(&mut self, data: u8) {
         let strobe = (data & 1) != 0;
 
@@ -3,45 +3,86 @@ use std::time::Duration;
 
 use egui::Ui;
 
-use crate::cartridge::{Cartridge, MapperType};
+use crate::bus::mapper::MapperTrait;
+use crate::cartridge::{Cartridge, CartridgeError, MapperType};
+use crate::controller::Input;
 use crate::cpu::Cpu;
 use crate::gui::DebugInfo;
+use crate::ppu::{PaletteError, PaletteSource, PaletteTable};
+use crate::region::Region;
+use crate::resampler::Resampler;
 use crate::util::Config;
+use crate::video::{PaletteFilter, VideoFilter};
 use crate::{State, OSCILLOSCOPE_DEPTH, OSCILLOSCOPE_SAMPLES};
 
+/// Everything a frontend needs to present one emulated frame
+pub struct FrameOutput<'a> {
+    /// 256x240 raw pixels: color indices with the emphasis bits above them
+    pub pixels: &'a [u16],
+    /// Color clock phase the frame started on
+    pub ntsc_phase: u8,
+    /// Count of frames the PPU has finished, which stays the same while emulation is paused
+    pub frame: usize,
+    /// The frame as RGBA, from the video stage
+    pub framebuffer: &'a [u32],
+    /// Width of `framebuffer`, which depends on the video stage. It's always 240 lines high.
+    pub width: usize,
+    /// Mono samples at `SAMPLERATE`
+    pub audio_samples: Vec<f32>,
+}
+
 pub struct EmulatorCore {
     pub cpu: Box<Cpu>,
     pub cycles_per_frame: usize,
     pub mapper_type: MapperType,
+    pub palette: PaletteSource,
+    pub region: Region,
     pub request_termination: bool,
     pub running: bool,
     pub state: State,
+    audio_samples: Vec<f32>,
     avg_fps: f64,
     fps: f64,
     // For calculating the number of cycles to run this frame
-    adjust: i8,
+    adjust: isize,
     frame: usize,
+    resampler: Resampler,
+    video: Box<dyn VideoFilter>,
     // For oscilloscope
     pub sample_buffers: [Vec<f32>; 6],
 }
 
 impl EmulatorCore {
-    pub fn new(config: &Config, clockrate: u32, test: bool) -> Result<Self, Box<dyn Error>> {
+    /// Loads the ROM in `config`. The region comes from the ROM header unless `config` overrides
+    /// it.
+    pub fn new(config: &Config, test: bool) -> Result<Self, CartridgeError> {
         let cartridge = Cartridge::new(&config.filename)?;
 
         let mapper_type = cartridge.mapper_type;
+        let region = config
+            .region
+            .unwrap_or_else(|| Region::from_timing(cartridge.timing));
+        let (cycles, frames) = region.cycles_per_frame();
+
+        let mut cpu = Box::new(Cpu::new(config, cartridge, region, test));
+        cpu.bus.ppu().no_sprite_limit = config.no_sprite_limit;
 
         Ok(Self {
-            cpu: Box::new(Cpu::new(config, cartridge, clockrate, test)),
-            cycles_per_frame: (341 * 262) / 3,
+            cpu,
+            cycles_per_frame: cycles / frames,
             mapper_type,
+            palette: PaletteSource::BuiltIn,
+            region,
             request_termination: false,
             running: true,
             state: State::Running,
+            audio_samples: Vec::new(),
             avg_fps: 60.0,
             fps: 60.0,
             adjust: 0,
             frame: 0,
+            resampler: Resampler::new(region.clockrate()),
+            video: Box::new(PaletteFilter::new(PaletteTable::new())),
             sample_buffers: [
                 vec![(OSCILLOSCOPE_DEPTH / 2) as f32; OSCILLOSCOPE_SAMPLES],
                 vec![(OSCILLOSCOPE_DEPTH / 2) as f32; OSCILLOSCOPE_SAMPLES],
@@ -60,20 +101,158 @@ impl EmulatorCore {
             let cpu_cyc = self.cpu.cyc;
             self.cpu.cyc = 0;
 
-            (cpu_cyc as isize - cycles_per_frame) as i8
+            cpu_cyc as isize - cycles_per_frame
         };
 
-        self.frame = (self.frame + 1) % 3;
+        let (cycles, frames) = self.region.cycles_per_frame();
+
+        self.frame = (self.frame + 1) % frames;
 
+        // Frames aren't a whole number of cycles, so the remainder is made up every few frames
         if self.frame == 0 {
-            self.adjust = 2;
+            self.adjust = (cycles % frames) as isize;
         } else {
             self.adjust = 0;
         }
 
         self.adjust -= rem;
 
-        self.cycles_per_frame = ((341 * 262) / 3 + self.adjust as i32) as usize;
+        self.cycles_per_frame = ((cycles / frames) as isize + self.adjust) as usize;
+    }
+
+    /// Applies `inputs` to the two controllers and runs the emulator until the end of the frame
+    /// (or until it halts when single-stepping)
+    pub fn run_frame(&mut self, inputs: [Input; 2]) -> Result<FrameOutput<'_>, Box<dyn Error>> {
+        for (port, input) in inputs.into_iter().enumerate() {
+            self.cpu.bus.controller(port).set_input(input);
+        }
+
+        self.update()?;
+
+        let audio_samples = std::mem::take(&mut self.audio_samples);
+
+        let ppu = self.cpu.bus.ppu();
+        let width = self.video.width();
+
+        Ok(FrameOutput {
+            pixels: &ppu.pixels,
+            ntsc_phase: ppu.ntsc_phase,
+            frame: ppu.frame,
+            framebuffer: self.video.apply(&ppu.pixels, ppu.ntsc_phase),
+            width,
+            audio_samples,
+        })
+    }
+
+    /// Continues from the save state `cpu`. Settings like the sprite limit, and what of the
+    /// cartridge is battery-backed, stay as they are rather than coming from the save state.
+    pub fn load_state(&mut self, cpu: &Cpu) {
+        let no_sprite_limit = self.cpu.bus.ppu().no_sprite_limit;
+        let nvram = self.cpu.bus.bus().nvram.clone();
+
+        *self.cpu = cpu.clone();
+        self.cpu.bus.restore();
+
+        let slots = self.cpu.bus.nametables();
+        self.cpu.bus.map_nametables(slots);
+        self.cpu.bus.ppu().no_sprite_limit = no_sprite_limit;
+        self.cpu.bus.bus().nvram = nvram;
+    }
+
+    /// Shows frames through a palette from `source`, replacing the current video stage. Nothing
+    /// changes if `source` can't be loaded.
+    pub fn set_palette(&mut self, source: PaletteSource) -> Result<(), PaletteError> {
+        let palette = PaletteTable::load(&source)?;
+
+        self.cpu.bus.ppu().set_palette(palette.clone());
+        self.video = Box::new(PaletteFilter::new(palette));
+        self.palette = source;
+
+        Ok(())
+    }
+
+    /// Replaces the stage that turns the PPU's raw pixels into RGBA
+    pub fn set_video_filter(&mut self, filter: Box<dyn VideoFilter>) {
+        self.video = filter;
+    }
+
+    /// Persists battery-backed RAM next to the ROM
+    pub fn save_data(&mut self) {
+        self.cpu.bus.save_data();
+    }
+
+    pub fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut old_state = self.state;
+        let mut new_state = old_state;
+
+        loop {
+            match new_state {
+                State::Running => {
+                    while new_state != State::RestartFrame {
+                        old_state = new_state;
+
+                        new_state = self.execute(new_state)?;
+                    }
+                }
+                State::SingleStep => {
+                    old_state = new_state;
+
+                    new_state = self.execute(new_state)?;
+
+                    if new_state != State::RestartFrame {
+                        new_state = State::Halted
+                    }
+                }
+                State::StepScanline => {
+                    loop {
+                        old_state = new_state;
+
+                        let ppu_cyc = self.cpu.bus.ppu().dot;
+
+                        new_state = self.execute(new_state)?;
+
+                        if self.cpu.bus.ppu().dot < ppu_cyc || new_state == State::RestartFrame {
+                            break;
+                        }
+                    }
+
+                    if new_state != State::RestartFrame {
+                        new_state = State::Halted;
+                    }
+                }
+                State::StepFrame => {
+                    loop {
+                        old_state = new_state;
+
+                        new_state = self.execute(new_state)?;
+
+                        if self.cpu.cyc >= self.cycles_per_frame || new_state == State::RestartFrame
+                        {
+                            break;
+                        }
+                    }
+
+                    if new_state != State::RestartFrame {
+                        new_state = State::Halted;
+                    }
+                }
+                State::RestartFrame => {
+                    new_state = if old_state != State::Running {
+                        State::Halted
+                    } else {
+                        State::Running
+                    };
+
+                    self.adjust_cycles_per_frame();
+                    break;
+                }
+                _ => break,
+            }
+        }
+
+        self.state = new_state;
+
+        Ok(())
     }
 
     pub fn update_fps(&mut self, dt: Duration) {
@@ -81,10 +260,53 @@ impl EmulatorCore {
 
         self.avg_fps = (self.avg_fps + self.fps) / 2.0;
     }
+
+    fn execute(&mut self, state: State) -> Result<State, Box<dyn Error>> {
+        self.cpu.fetch_decode_and_execute()?;
+
+        self.update_audio_buffer();
+
+        let dma = self.cpu.bus.bus().dma_interrupt;
+
+        if let Some(page) = dma {
+            self.cpu.dma(page);
+            self.cpu.bus.bus().dma_interrupt = None;
+        }
+
+        Ok(if self.cpu.cyc > self.cycles_per_frame {
+            State::RestartFrame
+        } else {
+            state
+        })
+    }
+
+    fn update_audio_buffer(&mut self) {
+        let sample_buf = std::mem::take(&mut self.cpu.sample_buf);
+
+        for samples in sample_buf {
+            if let Some(sample) = self.resampler.push_sample(&samples) {
+                self.audio_samples.push(sample);
+            }
+        }
+
+        if let Some(output) = self.resampler.output.take() {
+            let diameter = OSCILLOSCOPE_DEPTH / 2;
+
+            for (channel, output) in output.iter().enumerate() {
+                let buf = &mut self.sample_buffers[channel];
+
+                // Nothing drains the buffers unless the debugger is open
+                if buf.len() < OSCILLOSCOPE_SAMPLES {
+                    buf.push(diameter as f32 - output * diameter as f32 * 0.5);
+                }
+            }
+        }
+    }
 }
 
 impl DebugInfo for EmulatorCore {
     fn print(&self, ui: &mut Ui) {
         ui.label(format!("FPS: {:.3}", self.fps));
+        ui.label(format!("Region: {}", self.region));
     }
 }
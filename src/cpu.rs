@@ -12,9 +12,11 @@ use crate::bus::mapper::{
     Mapper, Mapper0, Mapper1, Mapper2, Mapper3, Mapper4, Mapper7, MapperTrait, MockBus,
 };
 use crate::cartridge::{Cartridge, MapperType};
+use crate::region::Region;
 use crate::util::bit::Bit;
 use crate::util::crosses_page;
-use crate::Config;
+use crate::util::Config;
+use interrupts::Interrupts;
 
 mod arithmetic;
 mod bitwise;
@@ -29,6 +31,7 @@ mod stack;
 pub mod status;
 mod store;
 mod transfer;
+mod unofficial;
 
 #[derive(Clone, Copy)]
 #[repr(usize)]
@@ -60,42 +63,49 @@ pub struct Cpu {
     pub bus: Mapper,
     pub cyc: usize,
     pub disasm: bool,
+    /// Address of the KIL opcode that halted the CPU, cleared on reset
+    #[serde(default)]
+    pub jammed: Option<u16>,
     pub nmi_acknowledged: bool,
     pub pc: u16,
     pub p: Status,
     pub sample_buf: Vec<[f32; 6]>,
     config: Config,
+    #[serde(default)]
+    interrupts: Interrupts,
     real_cyc: usize,
     regs: [u8; 3],
     sp: u8,
 }
 
 impl Cpu {
-    pub fn new(config: &Config, cartridge: Cartridge, clockrate: u32, mock: bool) -> Cpu {
+    pub fn new(config: &Config, cartridge: Cartridge, region: Region, mock: bool) -> Cpu {
         Cpu {
             config: config.clone(),
             bus: {
                 let mapper_type = cartridge.mapper_type;
 
                 if mock {
-                    Mapper::MockBus(MockBus::new(cartridge, clockrate))
+                    Mapper::MockBus(MockBus::new(cartridge, region))
                 } else {
                     match mapper_type {
-                        MapperType::Nrom => Mapper::Mapper0(Mapper0::new(cartridge, clockrate)),
+                        MapperType::Nrom => Mapper::Mapper0(Mapper0::new(cartridge, region)),
                         MapperType::MMC1 => {
-                            Mapper::Mapper1(Mapper1::new(cartridge, config, clockrate))
+                            Mapper::Mapper1(Mapper1::new(cartridge, config, region))
                         }
-                        MapperType::Uxrom => Mapper::Mapper2(Mapper2::new(cartridge, clockrate)),
-                        MapperType::Cnrom => Mapper::Mapper3(Mapper3::new(cartridge, clockrate)),
+                        MapperType::Uxrom => Mapper::Mapper2(Mapper2::new(cartridge, region)),
+                        MapperType::Cnrom => Mapper::Mapper3(Mapper3::new(cartridge, region)),
                         MapperType::MMC3 => {
-                            Mapper::Mapper4(Mapper4::new(cartridge, config, clockrate))
+                            Mapper::Mapper4(Mapper4::new(cartridge, config, region))
                         }
-                        MapperType::Axrom => Mapper::Mapper7(Mapper7::new(cartridge, clockrate)),
+                        MapperType::Axrom => Mapper::Mapper7(Mapper7::new(cartridge, region)),
                     }
                 }
             },
             cyc: 0,
             disasm: false,
+            interrupts: Interrupts::default(),
+            jammed: None,
             nmi_acknowledged: false,
             pc: 0,
             p: Status::new(),
@@ -106,65 +116,180 @@ impl Cpu {
         }
     }
 
+    /// OAM DMA, started by a write to $4014. The CPU is halted on the fetch of the next opcode.
     pub fn dma(&mut self, page: u8) {
-        if self.cyc % 2 == 1 {
-            self.tick(1);
+        self.run_dma(self.pc, Some(page));
+    }
+
+    /// Halts the CPU on its read of `addr` while the DMA units use the bus: DMC DMA whenever the
+    /// sample buffer is empty, and OAM DMA when `oam_page` is given. The halted CPU keeps
+    /// repeating its read, which is how DMA can clock the controllers or bump the $2007 address.
+    ///
+    /// DMA reads ("gets") only happen on even cycles and OAM writes ("puts") on odd ones. The DMC
+    /// needs a halt and a dummy cycle before its get, so it stalls a lone read for 3 or 4 cycles
+    /// and steals 2 cycles from an OAM transfer.
+    fn run_dma(&mut self, addr: u16, oam_page: Option<u8>) {
+        // The controllers see a run of reads as one, as /OE stays asserted for all of them
+        let repeats_read = !matches!(addr, 0x4016 | 0x4017);
+
+        let mut dmc = self.bus.apu().dmc.buffer_should_be_filled();
+        let mut dmc_delay = 2;
+        let mut oam_addr = (oam_page.unwrap_or(0) as u16) << 8;
+        let mut oam_left = if oam_page.is_some() { 0x100 } else { 0 };
+        let mut oam_data = None;
+
+        // Halt cycle
+        self.bus.read_u8(addr);
+        self.dma_tick(&mut dmc, &mut dmc_delay);
+
+        while dmc || oam_left > 0 {
+            let get = self.real_cyc.is_multiple_of(2);
+
+            if get && dmc && dmc_delay == 0 {
+                let sample_addr = self.bus.apu().dmc.addr;
+                let data = self.bus.read_u8(sample_addr);
+
+                self.bus.bus().fill_dmc_buffer(data);
+
+                dmc = false;
+            } else if get && oam_left > 0 {
+                oam_data = Some(self.bus.read_u8(oam_addr));
+                oam_addr = oam_addr.wrapping_add(1);
+            } else if let (false, Some(data)) = (get, oam_data) {
+                self.bus.ppu().oam.write(data);
+
+                oam_data = None;
+                oam_left -= 1;
+            } else if repeats_read {
+                // Dummy or alignment cycle
+                self.bus.read_u8(addr);
+            }
+
+            self.dma_tick(&mut dmc, &mut dmc_delay);
         }
+    }
 
-        let addr = ((page as u16) << 8) as usize;
+    /// Counts down the DMC's halt and dummy cycles and picks up DMC requests made during OAM DMA
+    fn dma_tick(&mut self, dmc: &mut bool, dmc_delay: &mut u8) {
+        self.tick(1);
 
-        for addr in addr..addr + 0x100 {
-            let data = self.bus.read_u8(addr as u16);
-            self.tick(1);
+        *dmc_delay = dmc_delay.saturating_sub(1);
 
-            self.bus.ppu().oam.write(data);
-            self.tick(1);
+        if !*dmc && self.bus.apu().dmc.buffer_should_be_filled() {
+            *dmc = true;
+            *dmc_delay = 2;
         }
     }
 
-    fn ea_zp(&self, addr: u8) -> u16 {
-        addr as u16
+    /// Resolves the address `operand` refers to in `mode`, doing the bus accesses of the
+    /// addressing cycles. Stores and read-modify-write instructions (`write`) always take the cycle
+    /// that fixes up the high byte of an indexed address, reads only when a page is crossed.
+    fn effective_addr(&mut self, operand: u16, mode: AddressingMode, write: bool) -> u16 {
+        match mode {
+            AddressingMode::ZeroPage => operand & 0xFF,
+            AddressingMode::ZeroPageX => self.zp_indexed(operand as u8, X),
+            AddressingMode::ZeroPageY => self.zp_indexed(operand as u8, Y),
+            AddressingMode::Absolute => operand,
+            AddressingMode::AbsoluteX => self.abs_indexed(operand, X, write),
+            AddressingMode::AbsoluteY => self.abs_indexed(operand, Y, write),
+            AddressingMode::IndexedIndirect => {
+                let ptr = operand as u8;
+
+                self.dummy_read(ptr as u16);
+
+                let ptr = ptr.wrapping_add(self.regs[X as usize]);
+
+                self.read_zp_ptr(ptr)
+            }
+            AddressingMode::IndirectIndexed => {
+                let base = self.read_zp_ptr(operand as u8);
+
+                self.abs_indexed(base, Y, write)
+            }
+            _ => unreachable!(),
+        }
     }
 
-    fn ea_zp_idx(&self, addr: u8, idx: u8) -> u16 {
-        addr.wrapping_add(idx) as u16
+    fn zp_indexed(&mut self, addr: u8, idx: Register) -> u16 {
+        self.dummy_read(addr as u16);
+
+        addr.wrapping_add(self.regs[idx as usize]) as u16
     }
 
-    fn ea_abs(&self, addr: u16) -> u16 {
-        addr
+    fn abs_indexed(&mut self, base: u16, idx: Register, write: bool) -> u16 {
+        let offset = self.regs[idx as usize];
+        let target = base.wrapping_add(offset as u16);
+
+        if write || crosses_page(base, offset as i32) {
+            // The index is added to the low byte first, so this reads from the wrong page when
+            // crossing one
+            self.dummy_read((base & 0xFF00) | (target & 0xFF));
+        }
+
+        target
     }
 
-    fn ea_abs_idx(&self, addr: u16, idx: u8) -> u16 {
-        addr.wrapping_add(idx as u16)
+    fn read_zp_ptr(&mut self, ptr: u8) -> u16 {
+        let l = self.read(ptr as u16);
+        let h = self.read(ptr.wrapping_add(1) as u16);
+
+        ((h as u16) << 8) | (l as u16)
     }
 
-    fn ea_ind(&mut self, addr: u16) -> (u16, u16) {
-        let l = (addr & 0xFF) as u8;
-        let h = ((addr & 0xFF00) >> 8) as u8;
-        let adl = ((h as u16) << 8) | (l as u16);
-        let adh = ((h as u16) << 8) | (l.wrapping_add(1) as u16);
+    fn read_operand(&mut self, operand: u16, mode: AddressingMode) -> u8 {
+        match mode {
+            AddressingMode::Immediate => operand as u8,
+            _ => {
+                let addr = self.effective_addr(operand, mode, false);
 
-        (adl, adh)
+                self.read(addr)
+            }
+        }
     }
 
-    fn ea_idx_ind(&mut self, addr: u8, idx: u8) -> u16 {
-        let l = self.bus.read_u8(addr.wrapping_add(idx) as u16);
-        let h = self
-            .bus
-            .read_u8(addr.wrapping_add(1).wrapping_add(idx) as u16);
-        ((h as u16) << 8) | (l as u16)
+    fn write_operand(&mut self, operand: u16, mode: AddressingMode, data: u8) {
+        let addr = self.effective_addr(operand, mode, true);
+
+        self.write(addr, data);
     }
 
-    fn ea_ind_idx(&mut self, addr: u8, idx: u8) -> (u16, bool) {
-        let l = self.bus.read_u8(addr as u16);
-        let h = self.bus.read_u8(addr.wrapping_add(1) as u16);
-        let target = ((h as u16) << 8) | (l as u16);
-        let (_, cycle_penalty) = l.overflowing_add(idx);
+    /// Applies `op` to the operand and returns the result. Like the real CPU, the unmodified value
+    /// is written back once before the result.
+    fn read_modify_write(
+        &mut self,
+        operand: u16,
+        mode: AddressingMode,
+        op: fn(&mut Self, u8) -> u8,
+    ) -> u8 {
+        if let AddressingMode::Accumulator = mode {
+            self.implied();
+
+            let val = op(self, self.regs[A as usize]);
+
+            self.regs[A as usize] = val;
+
+            return val;
+        }
+
+        let addr = self.effective_addr(operand, mode, true);
+        let val = self.read(addr);
+
+        self.write(addr, val);
 
-        (target.wrapping_add(idx as u16), cycle_penalty)
+        let val = op(self, val);
+
+        self.write(addr, val);
+
+        val
     }
 
     pub fn fetch_decode_and_execute(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.interrupt_pending() {
+            self.interrupt();
+
+            return Ok(());
+        }
+
         if self.disasm {
             self.disasm();
         }
@@ -574,9 +699,10 @@ impl Cpu {
             }
             // JSR Oper
             0x20 => {
-                let addr = self.read_word_operand();
+                // The high byte is fetched last, after the return address has been pushed
+                let low = self.read_byte_operand();
 
-                self.jsr(addr)
+                self.jsr(low)
             }
             // LDA # Oper
             0xA9 => {
@@ -982,45 +1108,557 @@ impl Cpu {
             0x8A => self.txa(),
             // TXS
             0x9A => self.txs(),
-            _ => return Err(format!("Unimplemented instruction 0x{opcode:x?}").into()),
-        };
+            // Unofficial opcodes
+            // ALR # Oper
+            0x4B => {
+                let imm = self.read_byte_operand() as u16;
 
-        Ok(())
-    }
+                self.alr(imm)
+            }
+            // ANC # Oper
+            0xB => {
+                let imm = self.read_byte_operand() as u16;
 
-    pub fn handle_nmi(&mut self) {
-        // Delay NMI handling slightly to make the emulator a little bit more forgiving, mostly for Battletoads
-        self.tick(7 + 7);
-        self.push_u16(self.pc);
+                self.anc(imm)
+            }
+            // ANC # Oper
+            0x2B => {
+                let imm = self.read_byte_operand() as u16;
 
-        let flags = self.flags();
-        let flags = flags | 0b00100000;
-        let flags = flags & !0b00010000;
+                self.anc(imm)
+            }
+            // ARR # Oper
+            0x6B => {
+                let imm = self.read_byte_operand() as u16;
 
-        self.push(flags);
-        self.p[InterruptDisable as usize] = true;
-        self.pc = self.bus.read_u16(0xFFFA);
-    }
+                self.arr(imm)
+            }
+            // AXS # Oper
+            0xCB => {
+                let imm = self.read_byte_operand() as u16;
 
-    pub fn handle_irq(&mut self) {
-        if self.p[InterruptDisable as usize] {
-            return;
-        }
+                self.axs(imm)
+            }
+            // DCP Oper
+            0xC7 => {
+                let addr = self.read_byte_operand() as u16;
 
-        self.tick(7);
-        self.push_u16(self.pc);
+                self.dcp(addr, AddressingMode::ZeroPage)
+            }
+            // DCP Oper, X
+            0xD7 => {
+                let addr = self.read_byte_operand() as u16;
 
-        let flags = self.flags();
-        let flags = flags | 0b00100000;
-        let flags = flags & !0b00010000;
+                self.dcp(addr, AddressingMode::ZeroPageX)
+            }
+            // DCP Oper
+            0xCF => {
+                let addr = self.read_word_operand();
 
-        self.push(flags);
-        self.p[InterruptDisable as usize] = true;
-        self.pc = self.bus.read_u16(0xFFFE);
+                self.dcp(addr, AddressingMode::Absolute)
+            }
+            // DCP Oper, X
+            0xDF => {
+                let addr = self.read_word_operand();
+
+                self.dcp(addr, AddressingMode::AbsoluteX)
+            }
+            // DCP Oper, Y
+            0xDB => {
+                let addr = self.read_word_operand();
+
+                self.dcp(addr, AddressingMode::AbsoluteY)
+            }
+            // DCP (Oper, X)
+            0xC3 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.dcp(addr, AddressingMode::IndexedIndirect)
+            }
+            // DCP (Oper), Y
+            0xD3 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.dcp(addr, AddressingMode::IndirectIndexed)
+            }
+            // ISC Oper
+            0xE7 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.isc(addr, AddressingMode::ZeroPage)
+            }
+            // ISC Oper, X
+            0xF7 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.isc(addr, AddressingMode::ZeroPageX)
+            }
+            // ISC Oper
+            0xEF => {
+                let addr = self.read_word_operand();
+
+                self.isc(addr, AddressingMode::Absolute)
+            }
+            // ISC Oper, X
+            0xFF => {
+                let addr = self.read_word_operand();
+
+                self.isc(addr, AddressingMode::AbsoluteX)
+            }
+            // ISC Oper, Y
+            0xFB => {
+                let addr = self.read_word_operand();
+
+                self.isc(addr, AddressingMode::AbsoluteY)
+            }
+            // ISC (Oper, X)
+            0xE3 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.isc(addr, AddressingMode::IndexedIndirect)
+            }
+            // ISC (Oper), Y
+            0xF3 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.isc(addr, AddressingMode::IndirectIndexed)
+            }
+            // LAS Oper, Y
+            0xBB => {
+                let addr = self.read_word_operand();
+
+                self.las(addr, AddressingMode::AbsoluteY)
+            }
+            // LAX # Oper
+            0xAB => {
+                let imm = self.read_byte_operand() as u16;
+
+                self.lax(imm, AddressingMode::Immediate)
+            }
+            // LAX Oper
+            0xA7 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.lax(addr, AddressingMode::ZeroPage)
+            }
+            // LAX Oper, Y
+            0xB7 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.lax(addr, AddressingMode::ZeroPageY)
+            }
+            // LAX Oper
+            0xAF => {
+                let addr = self.read_word_operand();
+
+                self.lax(addr, AddressingMode::Absolute)
+            }
+            // LAX Oper, Y
+            0xBF => {
+                let addr = self.read_word_operand();
+
+                self.lax(addr, AddressingMode::AbsoluteY)
+            }
+            // LAX (Oper, X)
+            0xA3 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.lax(addr, AddressingMode::IndexedIndirect)
+            }
+            // LAX (Oper), Y
+            0xB3 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.lax(addr, AddressingMode::IndirectIndexed)
+            }
+            // NOP
+            0x1A => self.nop(),
+            // NOP
+            0x3A => self.nop(),
+            // NOP
+            0x5A => self.nop(),
+            // NOP
+            0x7A => self.nop(),
+            // NOP
+            0xDA => self.nop(),
+            // NOP
+            0xFA => self.nop(),
+            // NOP # Oper
+            0x80 => {
+                let imm = self.read_byte_operand() as u16;
+
+                self.nop_read(imm, AddressingMode::Immediate)
+            }
+            // NOP # Oper
+            0x82 => {
+                let imm = self.read_byte_operand() as u16;
+
+                self.nop_read(imm, AddressingMode::Immediate)
+            }
+            // NOP # Oper
+            0x89 => {
+                let imm = self.read_byte_operand() as u16;
+
+                self.nop_read(imm, AddressingMode::Immediate)
+            }
+            // NOP # Oper
+            0xC2 => {
+                let imm = self.read_byte_operand() as u16;
+
+                self.nop_read(imm, AddressingMode::Immediate)
+            }
+            // NOP # Oper
+            0xE2 => {
+                let imm = self.read_byte_operand() as u16;
+
+                self.nop_read(imm, AddressingMode::Immediate)
+            }
+            // NOP Oper
+            0x4 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.nop_read(addr, AddressingMode::ZeroPage)
+            }
+            // NOP Oper
+            0x44 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.nop_read(addr, AddressingMode::ZeroPage)
+            }
+            // NOP Oper
+            0x64 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.nop_read(addr, AddressingMode::ZeroPage)
+            }
+            // NOP Oper, X
+            0x14 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.nop_read(addr, AddressingMode::ZeroPageX)
+            }
+            // NOP Oper, X
+            0x34 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.nop_read(addr, AddressingMode::ZeroPageX)
+            }
+            // NOP Oper, X
+            0x54 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.nop_read(addr, AddressingMode::ZeroPageX)
+            }
+            // NOP Oper, X
+            0x74 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.nop_read(addr, AddressingMode::ZeroPageX)
+            }
+            // NOP Oper, X
+            0xD4 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.nop_read(addr, AddressingMode::ZeroPageX)
+            }
+            // NOP Oper, X
+            0xF4 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.nop_read(addr, AddressingMode::ZeroPageX)
+            }
+            // NOP Oper
+            0xC => {
+                let addr = self.read_word_operand();
+
+                self.nop_read(addr, AddressingMode::Absolute)
+            }
+            // NOP Oper, X
+            0x1C => {
+                let addr = self.read_word_operand();
+
+                self.nop_read(addr, AddressingMode::AbsoluteX)
+            }
+            // NOP Oper, X
+            0x3C => {
+                let addr = self.read_word_operand();
+
+                self.nop_read(addr, AddressingMode::AbsoluteX)
+            }
+            // NOP Oper, X
+            0x5C => {
+                let addr = self.read_word_operand();
+
+                self.nop_read(addr, AddressingMode::AbsoluteX)
+            }
+            // NOP Oper, X
+            0x7C => {
+                let addr = self.read_word_operand();
+
+                self.nop_read(addr, AddressingMode::AbsoluteX)
+            }
+            // NOP Oper, X
+            0xDC => {
+                let addr = self.read_word_operand();
+
+                self.nop_read(addr, AddressingMode::AbsoluteX)
+            }
+            // NOP Oper, X
+            0xFC => {
+                let addr = self.read_word_operand();
+
+                self.nop_read(addr, AddressingMode::AbsoluteX)
+            }
+            // RLA Oper
+            0x27 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.rla(addr, AddressingMode::ZeroPage)
+            }
+            // RLA Oper, X
+            0x37 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.rla(addr, AddressingMode::ZeroPageX)
+            }
+            // RLA Oper
+            0x2F => {
+                let addr = self.read_word_operand();
+
+                self.rla(addr, AddressingMode::Absolute)
+            }
+            // RLA Oper, X
+            0x3F => {
+                let addr = self.read_word_operand();
+
+                self.rla(addr, AddressingMode::AbsoluteX)
+            }
+            // RLA Oper, Y
+            0x3B => {
+                let addr = self.read_word_operand();
+
+                self.rla(addr, AddressingMode::AbsoluteY)
+            }
+            // RLA (Oper, X)
+            0x23 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.rla(addr, AddressingMode::IndexedIndirect)
+            }
+            // RLA (Oper), Y
+            0x33 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.rla(addr, AddressingMode::IndirectIndexed)
+            }
+            // RRA Oper
+            0x67 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.rra(addr, AddressingMode::ZeroPage)
+            }
+            // RRA Oper, X
+            0x77 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.rra(addr, AddressingMode::ZeroPageX)
+            }
+            // RRA Oper
+            0x6F => {
+                let addr = self.read_word_operand();
+
+                self.rra(addr, AddressingMode::Absolute)
+            }
+            // RRA Oper, X
+            0x7F => {
+                let addr = self.read_word_operand();
+
+                self.rra(addr, AddressingMode::AbsoluteX)
+            }
+            // RRA Oper, Y
+            0x7B => {
+                let addr = self.read_word_operand();
+
+                self.rra(addr, AddressingMode::AbsoluteY)
+            }
+            // RRA (Oper, X)
+            0x63 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.rra(addr, AddressingMode::IndexedIndirect)
+            }
+            // RRA (Oper), Y
+            0x73 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.rra(addr, AddressingMode::IndirectIndexed)
+            }
+            // SAX Oper
+            0x87 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.sax(addr, AddressingMode::ZeroPage)
+            }
+            // SAX Oper, Y
+            0x97 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.sax(addr, AddressingMode::ZeroPageY)
+            }
+            // SAX Oper
+            0x8F => {
+                let addr = self.read_word_operand();
+
+                self.sax(addr, AddressingMode::Absolute)
+            }
+            // SAX (Oper, X)
+            0x83 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.sax(addr, AddressingMode::IndexedIndirect)
+            }
+            // SBC # Oper
+            0xEB => {
+                let imm = self.read_byte_operand() as u16;
+
+                self.sbc(imm, AddressingMode::Immediate)
+            }
+            // SHA Oper, Y
+            0x9F => {
+                let addr = self.read_word_operand();
+
+                self.sha(addr, AddressingMode::AbsoluteY)
+            }
+            // SHA (Oper), Y
+            0x93 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.sha(addr, AddressingMode::IndirectIndexed)
+            }
+            // SHX Oper, Y
+            0x9E => {
+                let addr = self.read_word_operand();
+
+                self.shx(addr)
+            }
+            // SHY Oper, X
+            0x9C => {
+                let addr = self.read_word_operand();
+
+                self.shy(addr)
+            }
+            // SLO Oper
+            0x7 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.slo(addr, AddressingMode::ZeroPage)
+            }
+            // SLO Oper, X
+            0x17 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.slo(addr, AddressingMode::ZeroPageX)
+            }
+            // SLO Oper
+            0xF => {
+                let addr = self.read_word_operand();
+
+                self.slo(addr, AddressingMode::Absolute)
+            }
+            // SLO Oper, X
+            0x1F => {
+                let addr = self.read_word_operand();
+
+                self.slo(addr, AddressingMode::AbsoluteX)
+            }
+            // SLO Oper, Y
+            0x1B => {
+                let addr = self.read_word_operand();
+
+                self.slo(addr, AddressingMode::AbsoluteY)
+            }
+            // SLO (Oper, X)
+            0x3 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.slo(addr, AddressingMode::IndexedIndirect)
+            }
+            // SLO (Oper), Y
+            0x13 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.slo(addr, AddressingMode::IndirectIndexed)
+            }
+            // SRE Oper
+            0x47 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.sre(addr, AddressingMode::ZeroPage)
+            }
+            // SRE Oper, X
+            0x57 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.sre(addr, AddressingMode::ZeroPageX)
+            }
+            // SRE Oper
+            0x4F => {
+                let addr = self.read_word_operand();
+
+                self.sre(addr, AddressingMode::Absolute)
+            }
+            // SRE Oper, X
+            0x5F => {
+                let addr = self.read_word_operand();
+
+                self.sre(addr, AddressingMode::AbsoluteX)
+            }
+            // SRE Oper, Y
+            0x5B => {
+                let addr = self.read_word_operand();
+
+                self.sre(addr, AddressingMode::AbsoluteY)
+            }
+            // SRE (Oper, X)
+            0x43 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.sre(addr, AddressingMode::IndexedIndirect)
+            }
+            // SRE (Oper), Y
+            0x53 => {
+                let addr = self.read_byte_operand() as u16;
+
+                self.sre(addr, AddressingMode::IndirectIndexed)
+            }
+            // TAS Oper, Y
+            0x9B => {
+                let addr = self.read_word_operand();
+
+                self.tas(addr)
+            }
+            // XAA # Oper
+            0x8B => {
+                let imm = self.read_byte_operand() as u16;
+
+                self.xaa(imm)
+            }
+            // KIL
+            0x2 | 0x12 | 0x22 | 0x32 | 0x42 | 0x52 | 0x62 | 0x72 | 0x92 | 0xB2 | 0xD2 | 0xF2 => {
+                self.jam()
+            }
+        };
+
+        Ok(())
+    }
+
+    /// The second cycle of single-byte instructions reads the next byte and throws it away
+    fn implied(&mut self) {
+        self.dummy_read(self.pc);
     }
 
     fn nop(&mut self) {
-        self.tick(2);
+        self.implied();
     }
 
     fn pull(&mut self) -> u8 {
@@ -1028,7 +1666,7 @@ impl Cpu {
 
         let sp = self.sp as u16;
 
-        self.bus.read_u8(0x100 + sp)
+        self.read(0x100 + sp)
     }
 
     fn pull_u16(&mut self) -> u16 {
@@ -1041,7 +1679,7 @@ impl Cpu {
     fn push(&mut self, data: u8) {
         let sp = self.sp as u16;
 
-        self.bus.write_u8(0x100 + sp, data);
+        self.write(0x100 + sp, data);
 
         self.sp = self.sp.wrapping_sub(1);
     }
@@ -1054,44 +1692,27 @@ impl Cpu {
         self.push(low);
     }
 
-    fn read_zp(&mut self, addr: u8) -> u8 {
-        self.bus.read_u8(self.ea_zp(addr))
-    }
-
-    fn read_zp_idx(&mut self, addr: u8, idx: u8) -> u8 {
-        self.bus.read_u8(self.ea_zp_idx(addr, idx))
-    }
-
-    fn read_abs(&mut self, addr: u16) -> u8 {
-        self.bus.read_u8(self.ea_abs(addr))
-    }
+    /// Reads the stack without popping, which the CPU does while it adjusts the stack pointer
+    fn peek_stack(&mut self) {
+        let sp = self.sp as u16;
 
-    fn read_abs_idx(&mut self, addr: u16, idx: u8) -> u8 {
-        self.bus.read_u8(self.ea_abs_idx(addr, idx))
+        self.dummy_read(0x100 + sp);
     }
 
     fn read_ind(&mut self, addr: u16) -> u16 {
-        let (adl, adh) = self.ea_ind(addr);
-        let l = self.bus.read_u8(adl);
-        let h = self.bus.read_u8(adh);
+        // The high byte is fetched without carrying into the page, e.g. JMP ($10FF) reads $10FF and $1000
+        let adh = (addr & 0xFF00) | (addr as u8).wrapping_add(1) as u16;
+        let l = self.read(addr);
+        let h = self.read(adh);
 
         ((h as u16) << 8) | (l as u16)
     }
 
-    fn read_idx_ind(&mut self, addr: u8, idx: u8) -> u8 {
-        let target = self.ea_idx_ind(addr, idx);
-
-        self.bus.read_u8(target)
-    }
-
-    fn read_ind_idx(&mut self, addr: u8, idx: u8) -> u8 {
-        let (target, cycle_penalty) = self.ea_ind_idx(addr, idx);
-
-        if cycle_penalty {
-            self.tick(1);
-        }
+    fn read_vector(&mut self, addr: u16) -> u16 {
+        let l = self.read(addr);
+        let h = self.read(addr + 1);
 
-        self.bus.read_u8(target)
+        ((h as u16) << 8) | (l as u16)
     }
 
     fn read_opcode(&mut self) -> u8 {
@@ -1103,30 +1724,52 @@ impl Cpu {
     }
 
     fn read_word_operand(&mut self) -> u16 {
-        self.read_u16()
+        let low = self.read_u8() as u16;
+        let high = self.read_u8() as u16;
+
+        (high << 8) | low
     }
 
     fn read_u8(&mut self) -> u8 {
-        let pc = self.pc;
-        let byte = self.bus.read_u8(pc);
+        let byte = self.read(self.pc);
 
         self.pc = self.pc.wrapping_add(1);
 
         byte
     }
 
-    fn read_u16(&mut self) -> u16 {
-        let pc = self.pc;
-        let word = self.bus.read_u16(pc);
+    /// One CPU cycle that reads from the bus. A pending DMC DMA halts the CPU here first, as DMA
+    /// can only take over on read cycles.
+    fn read(&mut self, addr: u16) -> u8 {
+        if self.bus.apu().dmc.buffer_should_be_filled() {
+            self.run_dma(addr, None);
+        }
+
+        let data = self.bus.read_u8(addr);
 
-        self.pc = self.pc.wrapping_add(2);
+        self.tick(1);
 
-        word
+        data
+    }
+
+    /// A read whose result the CPU ignores. It still has side effects on registers like $2007.
+    fn dummy_read(&mut self, addr: u16) {
+        self.read(addr);
+    }
+
+    /// One CPU cycle that writes to the bus
+    fn write(&mut self, addr: u16, data: u8) {
+        self.bus.write_u8(addr, data);
+
+        self.tick(1);
     }
 
     pub fn reset(&mut self) {
         self.tick(7);
 
+        self.interrupts = Interrupts::default();
+        self.jammed = None;
+
         self.p[InterruptDisable as usize] = true;
         self.sp = self.sp.wrapping_sub(3);
         self.pc = self.bus.read_u16(0xFFFC);
@@ -1134,21 +1777,13 @@ impl Cpu {
 
     pub fn tick(&mut self, cycles: usize) {
         for _ in 0..cycles {
-            self.bus.ppu().tick();
-            self.bus.ppu().tick();
-            self.bus.ppu().tick();
-            self.bus.apu().tick();
+            self.bus.bus().tick();
 
             let output = self.bus.apu().output();
 
             self.sample_buf.push(output);
 
-            if self.bus.apu().dmc.buffer_should_be_filled() {
-                let sample_addr = self.bus.apu().dmc.addr;
-                let data = self.bus.read_u8(sample_addr);
-
-                self.bus.apu().dmc.fill_buffer(data);
-            }
+            self.poll_interrupts();
         }
 
         self.cyc += cycles;
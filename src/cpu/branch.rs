@@ -36,35 +36,29 @@ impl Cpu {
 
     pub fn jmp(&mut self, addr: u16, mode: AddressingMode) {
         match mode {
-            AddressingMode::Absolute => {
-                self.tick(3);
-
-                self.pc = addr;
-            }
-            AddressingMode::Indirect => {
-                self.tick(5);
-
-                let target = self.read_ind(addr);
-
-                self.pc = target;
-            }
+            AddressingMode::Absolute => self.pc = addr,
+            AddressingMode::Indirect => self.pc = self.read_ind(addr),
             _ => unreachable!(),
         }
     }
 
-    pub fn jsr(&mut self, addr: u16) {
-        self.tick(6);
+    pub fn jsr(&mut self, low: u8) {
+        self.peek_stack();
+        // PC points at the high byte of the target, which is what RTS expects
+        self.push_u16(self.pc);
 
-        self.push_u16(self.pc.wrapping_sub(1));
+        let high = self.read_byte_operand();
 
-        self.pc = addr;
+        self.pc = ((high as u16) << 8) | (low as u16);
     }
 
     pub fn rts(&mut self) {
-        self.tick(6);
+        self.implied();
+        self.peek_stack();
 
         self.pc = self.pull_u16();
 
+        self.dummy_read(self.pc);
         self.pc = self.pc.wrapping_add(1);
     }
 
@@ -74,13 +68,14 @@ impl Cpu {
         let target = pc.wrapping_add(offset) as u16;
 
         if condition {
-            let cycles = if crosses_page(self.pc, offset) { 4 } else { 3 };
+            self.delay_irq_for_branch();
+            self.dummy_read(self.pc);
 
-            self.tick(cycles);
+            if crosses_page(self.pc, offset) {
+                self.dummy_read((self.pc & 0xFF00) | (target & 0xFF));
+            }
 
             self.pc = target;
-        } else {
-            self.tick(2);
         }
     }
 
@@ -31,6 +31,10 @@ impl DebugInfo for Cpu {
             let _ = ui.radio(self.p[Overflow as usize], "Overflow");
 
             let _ = ui.radio(self.p[Negative as usize], "Negative");
+
+            if let Some(pc) = self.jammed {
+                ui.label(format!("CPU jammed by KIL at {pc:04X}"));
+            }
         });
     }
 }
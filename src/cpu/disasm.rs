@@ -5,22 +5,26 @@ use crate::util::bit::Bit;
 use super::Register::*;
 
 const OPCODE_MAP: [&str; 256] = [
-    "BRK", "ORA", "", "", "", "ORA", "ASL", "", "PHP", "ORA", "ASL", "", "", "ORA", "ASL", "",
-    "BPL", "ORA", "", "", "", "ORA", "ASL", "", "CLC", "ORA", "", "", "", "ORA", "ASL", "", "JSR",
-    "AND", "", "", "BIT", "AND", "ROL", "", "PLP", "AND", "ROL", "", "BIT", "AND", "ROL", "",
-    "BMI", "AND", "", "", "", "AND", "ROL", "", "SEC", "AND", "", "", "", "AND", "ROL", "", "RTI",
-    "EOR", "", "", "", "EOR", "LSR", "", "PHA", "EOR", "LSR", "", "JMP", "EOR", "LSR", "", "BVC",
-    "EOR", "", "", "", "EOR", "LSR", "", "CLI", "EOR", "", "", "", "EOR", "LSR", "", "RTS", "ADC",
-    "", "", "", "ADC", "ROR", "", "PLA", "ADC", "ROR", "", "JMP", "ADC", "ROR", "", "BVS", "ADC",
-    "", "", "", "ADC", "ROR", "", "SEI", "ADC", "", "", "", "ADC", "ROR", "", "", "STA", "", "",
-    "STY", "STA", "STX", "", "DEY", "", "TXA", "", "STY", "STA", "STX", "", "BCC", "STA", "", "",
-    "STY", "STA", "STX", "", "TYA", "STA", "TXS", "", "", "STA", "", "", "LDY", "LDA", "LDX", "",
-    "LDY", "LDA", "LDX", "", "TAY", "LDA", "TAX", "", "LDY", "LDA", "LDX", "", "BCS", "LDA", "",
-    "", "LDY", "LDA", "LDX", "", "CLV", "LDA", "TSX", "", "LDY", "LDA", "LDX", "", "CPY", "CMP",
-    "", "", "CPY", "CMP", "DEC", "", "INY", "CMP", "DEX", "", "CPY", "CMP", "DEC", "", "BNE",
-    "CMP", "", "", "", "CMP", "DEC", "", "CLD", "CMP", "", "", "", "CMP", "DEC", "", "CPX", "SBC",
-    "", "", "CPX", "SBC", "INC", "", "INX", "SBC", "NOP", "", "CPX", "SBC", "INC", "", "BEQ",
-    "SBC", "", "", "", "SBC", "INC", "", "SED", "SBC", "", "", "", "SBC", "INC", "",
+    "BRK", "ORA", "KIL", "SLO", "NOP", "ORA", "ASL", "SLO", "PHP", "ORA", "ASL", "ANC", "NOP",
+    "ORA", "ASL", "SLO", "BPL", "ORA", "KIL", "SLO", "NOP", "ORA", "ASL", "SLO", "CLC", "ORA",
+    "NOP", "SLO", "NOP", "ORA", "ASL", "SLO", "JSR", "AND", "KIL", "RLA", "BIT", "AND", "ROL",
+    "RLA", "PLP", "AND", "ROL", "ANC", "BIT", "AND", "ROL", "RLA", "BMI", "AND", "KIL", "RLA",
+    "NOP", "AND", "ROL", "RLA", "SEC", "AND", "NOP", "RLA", "NOP", "AND", "ROL", "RLA", "RTI",
+    "EOR", "KIL", "SRE", "NOP", "EOR", "LSR", "SRE", "PHA", "EOR", "LSR", "ALR", "JMP", "EOR",
+    "LSR", "SRE", "BVC", "EOR", "KIL", "SRE", "NOP", "EOR", "LSR", "SRE", "CLI", "EOR", "NOP",
+    "SRE", "NOP", "EOR", "LSR", "SRE", "RTS", "ADC", "KIL", "RRA", "NOP", "ADC", "ROR", "RRA",
+    "PLA", "ADC", "ROR", "ARR", "JMP", "ADC", "ROR", "RRA", "BVS", "ADC", "KIL", "RRA", "NOP",
+    "ADC", "ROR", "RRA", "SEI", "ADC", "NOP", "RRA", "NOP", "ADC", "ROR", "RRA", "NOP", "STA",
+    "NOP", "SAX", "STY", "STA", "STX", "SAX", "DEY", "NOP", "TXA", "XAA", "STY", "STA", "STX",
+    "SAX", "BCC", "STA", "KIL", "SHA", "STY", "STA", "STX", "SAX", "TYA", "STA", "TXS", "TAS",
+    "SHY", "STA", "SHX", "SHA", "LDY", "LDA", "LDX", "LAX", "LDY", "LDA", "LDX", "LAX", "TAY",
+    "LDA", "TAX", "LAX", "LDY", "LDA", "LDX", "LAX", "BCS", "LDA", "KIL", "LAX", "LDY", "LDA",
+    "LDX", "LAX", "CLV", "LDA", "TSX", "LAS", "LDY", "LDA", "LDX", "LAX", "CPY", "CMP", "NOP",
+    "DCP", "CPY", "CMP", "DEC", "DCP", "INY", "CMP", "DEX", "AXS", "CPY", "CMP", "DEC", "DCP",
+    "BNE", "CMP", "KIL", "DCP", "NOP", "CMP", "DEC", "DCP", "CLD", "CMP", "NOP", "DCP", "NOP",
+    "CMP", "DEC", "DCP", "CPX", "SBC", "NOP", "ISC", "CPX", "SBC", "INC", "ISC", "INX", "SBC",
+    "NOP", "SBC", "CPX", "SBC", "INC", "ISC", "BEQ", "SBC", "KIL", "ISC", "NOP", "SBC", "INC",
+    "ISC", "SED", "SBC", "NOP", "ISC", "NOP", "SBC", "INC", "ISC",
 ];
 
 impl Cpu {
@@ -30,36 +34,37 @@ impl Cpu {
         let opcode = self.bus.read_u8(pc) as usize;
 
         let addr_mode = match opcode {
-            0x69 | 0x29 | 0xC9 | 0xE0 | 0xC0 | 0x49 | 0xA9 | 0xA2 | 0xA0 | 0x9 | 0xE9 => {
+            0x69 | 0x29 | 0xC9 | 0xE0 | 0xC0 | 0x49 | 0xA9 | 0xA2 | 0xA0 | 0x9 | 0xE9 | 0xB
+            | 0x2B | 0x4B | 0x6B | 0x80 | 0x82 | 0x89 | 0x8B | 0xAB | 0xC2 | 0xCB | 0xE2 | 0xEB => {
                 AddressingMode::Immediate
             }
             0x65 | 0x25 | 0x6 | 0x24 | 0xC5 | 0xE4 | 0xC4 | 0xC6 | 0x45 | 0xE6 | 0xA5 | 0xA6
-            | 0xA4 | 0x46 | 0x5 | 0x26 | 0x66 | 0xE5 | 0x85 | 0x86 | 0x84 => {
-                AddressingMode::ZeroPage
-            }
+            | 0xA4 | 0x46 | 0x5 | 0x26 | 0x66 | 0xE5 | 0x85 | 0x86 | 0x84 | 0x4 | 0x7 | 0x27
+            | 0x44 | 0x47 | 0x64 | 0x67 | 0x87 | 0xA7 | 0xC7 | 0xE7 => AddressingMode::ZeroPage,
             0x75 | 0x35 | 0x16 | 0xD5 | 0xD6 | 0x55 | 0xF6 | 0xB5 | 0xB4 | 0x56 | 0x15 | 0x36
-            | 0x76 | 0xF5 | 0x95 | 0x94 => AddressingMode::ZeroPageX,
-            0xB6 | 0x96 => AddressingMode::ZeroPageY,
+            | 0x76 | 0xF5 | 0x95 | 0x94 | 0x14 | 0x17 | 0x34 | 0x37 | 0x54 | 0x57 | 0x74 | 0x77
+            | 0xD4 | 0xD7 | 0xF4 | 0xF7 => AddressingMode::ZeroPageX,
+            0xB6 | 0x96 | 0x97 | 0xB7 => AddressingMode::ZeroPageY,
             0x6D | 0x2D | 0xE | 0x2C | 0xCD | 0xEC | 0xCC | 0xCE | 0x4D | 0xEE | 0x4C | 0x20
-            | 0xAD | 0xAE | 0xAC | 0x4E | 0xD | 0x2E | 0x6E | 0xED | 0x8D | 0x8E | 0x8C => {
-                AddressingMode::Absolute
-            }
+            | 0xAD | 0xAE | 0xAC | 0x4E | 0xD | 0x2E | 0x6E | 0xED | 0x8D | 0x8E | 0x8C | 0xC
+            | 0xF | 0x2F | 0x4F | 0x6F | 0x8F | 0xAF | 0xCF | 0xEF => AddressingMode::Absolute,
             0x7D | 0x3D | 0x1E | 0xDD | 0xDE | 0x5D | 0xFE | 0xBD | 0xBC | 0x5E | 0x1D | 0x3E
-            | 0x7E | 0xFD | 0x9D => AddressingMode::AbsoluteX,
-            0x79 | 0x39 | 0xD9 | 0x59 | 0xB9 | 0xBE | 0x19 | 0xF9 | 0x99 => {
-                AddressingMode::AbsoluteY
-            }
-            0x61 | 0x21 | 0xC1 | 0x41 | 0xA1 | 0x1 | 0xE1 | 0x81 => AddressingMode::IndexedIndirect,
-            0x71 | 0x31 | 0xD1 | 0x51 | 0xB1 | 0x11 | 0xF1 | 0x91 => {
-                AddressingMode::IndirectIndexed
-            }
+            | 0x7E | 0xFD | 0x9D | 0x1C | 0x1F | 0x3C | 0x3F | 0x5C | 0x5F | 0x7C | 0x7F | 0x9C
+            | 0xDC | 0xDF | 0xFC | 0xFF => AddressingMode::AbsoluteX,
+            0x79 | 0x39 | 0xD9 | 0x59 | 0xB9 | 0xBE | 0x19 | 0xF9 | 0x99 | 0x1B | 0x3B | 0x5B
+            | 0x7B | 0x9B | 0x9E | 0x9F | 0xBB | 0xBF | 0xDB | 0xFB => AddressingMode::AbsoluteY,
+            0x61 | 0x21 | 0xC1 | 0x41 | 0xA1 | 0x1 | 0xE1 | 0x81 | 0x3 | 0x23 | 0x43 | 0x63
+            | 0x83 | 0xA3 | 0xC3 | 0xE3 => AddressingMode::IndexedIndirect,
+            0x71 | 0x31 | 0xD1 | 0x51 | 0xB1 | 0x11 | 0xF1 | 0x91 | 0x13 | 0x33 | 0x53 | 0x73
+            | 0x93 | 0xB3 | 0xD3 | 0xF3 => AddressingMode::IndirectIndexed,
             0xA | 0x4A | 0x2A | 0x6A => AddressingMode::Accumulator,
             0x10 | 0x90 | 0xB0 | 0xF0 | 0x30 | 0xD0 | 0x50 | 0x70 => AddressingMode::Relative,
             0x0 | 0x18 | 0xD8 | 0x58 | 0xB8 | 0xCA | 0x88 | 0xE8 | 0xC8 | 0xEA | 0x48 | 0x8
             | 0x68 | 0x28 | 0x40 | 0x60 | 0xF8 | 0x38 | 0x78 | 0xAA | 0xA8 | 0x98 | 0xBA | 0x8A
-            | 0x9A => AddressingMode::Implied,
+            | 0x9A | 0x2 | 0x12 | 0x1A | 0x22 | 0x32 | 0x3A | 0x42 | 0x52 | 0x5A | 0x62 | 0x72
+            | 0x7A | 0x92 | 0xB2 | 0xD2 | 0xDA | 0xF2 | 0xFA => AddressingMode::Implied,
             0x6C => AddressingMode::Indirect,
-            _ => unimplemented!("Unimplemented instruction 0x{opcode:x?}"),
+            _ => unreachable!(),
         };
 
         let frame = self.bus.ppu().frame;
@@ -19,7 +19,7 @@ impl Cpu {
     }
 
     fn clear_flag(&mut self, flag: u8) {
-        self.tick(2);
+        self.implied();
 
         self.p[flag as usize] = false;
     }
@@ -49,7 +49,7 @@ impl Cpu {
     }
 
     fn set_flag(&mut self, flag: u8) {
-        self.tick(2);
+        self.implied();
 
         self.p[flag as usize] = true;
     }
@@ -1,10 +1,30 @@
+use serde::{Deserialize, Serialize};
+
 use super::*;
 
+const NMI_VECTOR: u16 = 0xFFFA;
+const IRQ_VECTOR: u16 = 0xFFFE;
+
+/// The interrupt inputs as sampled at the end of every cycle. An instruction is followed by an
+/// interrupt if one was pending on its second-to-last cycle.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Interrupts {
+    /// Level of /NMI on the previous cycle, for edge detection
+    nmi_line: bool,
+    nmi_pending: bool,
+    prev_nmi_pending: bool,
+    irq_pending: bool,
+    prev_irq_pending: bool,
+}
+
 impl Cpu {
     pub fn brk(&mut self) {
-        self.tick(7);
+        // BRK skips a padding byte, so it returns to the instruction after it
+        self.read_byte_operand();
 
-        self.push_u16(self.pc + 1);
+        self.push_u16(self.pc);
+
+        let vector = self.interrupt_vector();
 
         let flags = self.flags();
         let flags = flags | 0b00110000;
@@ -12,15 +32,79 @@ impl Cpu {
         self.push(flags);
         self.p[InterruptDisable as usize] = true;
 
-        self.pc = self.bus.read_u16(0xFFFE);
+        self.pc = self.read_vector(vector);
+
+        // The first instruction of the handler always runs, even if an NMI arrived during BRK
+        self.interrupts.prev_nmi_pending = false;
     }
 
     pub fn rti(&mut self) {
-        self.tick(6);
+        self.implied();
+        self.peek_stack();
 
         let flags = self.pull_flags();
 
         self.p = flags;
         self.pc = self.pull_u16();
     }
+
+    /// The hardware IRQ/NMI sequence, which is BRK without the padding byte and B flag
+    pub(super) fn interrupt(&mut self) {
+        self.dummy_read(self.pc);
+        self.dummy_read(self.pc);
+        self.push_u16(self.pc);
+
+        let vector = self.interrupt_vector();
+
+        let flags = self.flags();
+        let flags = flags | 0b00100000;
+        let flags = flags & !0b00010000;
+
+        self.push(flags);
+        self.p[InterruptDisable as usize] = true;
+
+        self.pc = self.read_vector(vector);
+    }
+
+    pub(super) fn interrupt_pending(&self) -> bool {
+        // KIL stops the CPU from responding to interrupts too
+        self.jammed.is_none()
+            && (self.interrupts.prev_nmi_pending || self.interrupts.prev_irq_pending)
+    }
+
+    /// Delays an IRQ that was raised during the operand fetch of a taken branch by one
+    /// instruction, since the branch doesn't poll again on its extra cycle
+    pub(super) fn delay_irq_for_branch(&mut self) {
+        if self.interrupts.irq_pending && !self.interrupts.prev_irq_pending {
+            self.interrupts.irq_pending = false;
+        }
+    }
+
+    pub(super) fn poll_interrupts(&mut self) {
+        let nmi_line = self.bus.ppu().nmi_line();
+        let irq_line = self.bus.bus().irq.level();
+        let interrupts = &mut self.interrupts;
+
+        interrupts.prev_nmi_pending = interrupts.nmi_pending;
+
+        if nmi_line && !interrupts.nmi_line {
+            interrupts.nmi_pending = true;
+        }
+
+        interrupts.nmi_line = nmi_line;
+
+        interrupts.prev_irq_pending = interrupts.irq_pending;
+        interrupts.irq_pending = irq_line && !self.p[InterruptDisable as usize];
+    }
+
+    /// An NMI detected before the flags are pushed hijacks the sequence of BRK and IRQs
+    fn interrupt_vector(&mut self) -> u16 {
+        if self.interrupts.nmi_pending {
+            self.interrupts.nmi_pending = false;
+
+            NMI_VECTOR
+        } else {
+            IRQ_VECTOR
+        }
+    }
 }
@@ -4,12 +4,12 @@ use super::*;
 
 impl Cpu {
     pub fn pha(&mut self) {
-        self.tick(3);
+        self.implied();
         self.push(self.regs[A as usize]);
     }
 
     pub fn php(&mut self) {
-        self.tick(3);
+        self.implied();
 
         let flags = self.flags();
         let flags = flags | 0b00110000;
@@ -19,7 +19,8 @@ impl Cpu {
 
     #[allow(unused_parens)]
     pub fn pla(&mut self) {
-        self.tick(4);
+        self.implied();
+        self.peek_stack();
 
         let val = self.pull();
 
@@ -30,7 +31,8 @@ impl Cpu {
     }
 
     pub fn plp(&mut self) {
-        self.tick(4);
+        self.implied();
+        self.peek_stack();
 
         let flags = self.pull_flags();
 
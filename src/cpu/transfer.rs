@@ -17,7 +17,7 @@ impl Cpu {
 
     #[allow(unused_parens)]
     pub fn tsx(&mut self) {
-        self.tick(2);
+        self.implied();
 
         let val = self.sp;
 
@@ -32,14 +32,14 @@ impl Cpu {
     }
 
     pub fn txs(&mut self) {
-        self.tick(2);
+        self.implied();
 
         self.sp = self.regs[X as usize];
     }
 
     #[allow(unused_parens)]
     fn transfer(&mut self, reg1: Register, reg2: Register) {
-        self.tick(2);
+        self.implied();
 
         let val = self.regs[reg1 as usize];
 
@@ -0,0 +1,180 @@
+use crate::util::bit::Bit;
+
+use super::*;
+
+// The unstable XAA and LXA opcodes OR A with a value that depends on the chip and temperature
+// before ANDing. The 2A03 is generally observed to use $FF.
+const MAGIC: u8 = 0xFF;
+
+impl Cpu {
+    pub fn alr(&mut self, imm: u16) {
+        self.bitand(imm as u8);
+
+        let val = self.shr(self.regs[A as usize]);
+
+        self.regs[A as usize] = val;
+    }
+
+    pub fn anc(&mut self, imm: u16) {
+        self.bitand(imm as u8);
+        self.p[Carry as usize] = self.p[Negative as usize];
+    }
+
+    #[allow(unused_parens)]
+    pub fn arr(&mut self, imm: u16) {
+        let carry = self.p[Carry as usize] as u8;
+        let val = ((self.regs[A as usize] & imm as u8) >> 1) | (carry << 7);
+
+        self.regs[A as usize] = val;
+
+        self.p[Zero as usize] = (val == 0);
+        self.p[Negative as usize] = val.bit(7);
+        self.p[Carry as usize] = val.bit(6);
+        self.p[Overflow as usize] = val.bit(6) ^ val.bit(5);
+    }
+
+    #[allow(unused_parens)]
+    pub fn axs(&mut self, imm: u16) {
+        let val = self.regs[A as usize] & self.regs[X as usize];
+        let result = val.wrapping_sub(imm as u8);
+
+        self.regs[X as usize] = result;
+
+        self.p[Zero as usize] = (result == 0);
+        self.p[Negative as usize] = result.bit(7);
+        self.p[Carry as usize] = (imm as u8 <= val);
+    }
+
+    pub fn dcp(&mut self, addr: u16, mode: AddressingMode) {
+        let val = self.read_modify_write(addr, mode, Self::decrement);
+
+        self.compare(A, val);
+    }
+
+    pub fn isc(&mut self, addr: u16, mode: AddressingMode) {
+        let val = self.read_modify_write(addr, mode, Self::increment);
+
+        self.subb(val);
+    }
+
+    /// Halts the CPU until the next reset
+    pub fn jam(&mut self) {
+        self.jammed = Some(self.pc.wrapping_sub(1));
+        self.pc = self.pc.wrapping_sub(1);
+    }
+
+    pub fn las(&mut self, addr: u16, mode: AddressingMode) {
+        let val = self.read_operand(addr, mode) & self.sp;
+
+        self.sp = val;
+        self.regs[X as usize] = val;
+        self.load(A, val);
+    }
+
+    pub fn lax(&mut self, addr: u16, mode: AddressingMode) {
+        let val = match mode {
+            AddressingMode::Immediate => {
+                (self.regs[A as usize] | MAGIC) & self.read_operand(addr, mode)
+            }
+            _ => self.read_operand(addr, mode),
+        };
+
+        self.regs[X as usize] = val;
+        self.load(A, val);
+    }
+
+    /// The unofficial NOPs still read their operand, which matters for registers like $2002
+    pub fn nop_read(&mut self, addr: u16, mode: AddressingMode) {
+        match mode {
+            AddressingMode::Implied => self.nop(),
+            _ => {
+                self.read_operand(addr, mode);
+            }
+        }
+    }
+
+    pub fn rla(&mut self, addr: u16, mode: AddressingMode) {
+        let val = self.read_modify_write(addr, mode, Self::lrotate);
+
+        self.bitand(val);
+    }
+
+    pub fn rra(&mut self, addr: u16, mode: AddressingMode) {
+        let val = self.read_modify_write(addr, mode, Self::rrotate);
+
+        self.addc(val);
+    }
+
+    pub fn sax(&mut self, addr: u16, mode: AddressingMode) {
+        let val = self.regs[A as usize] & self.regs[X as usize];
+
+        self.write_operand(addr, mode, val);
+    }
+
+    pub fn sha(&mut self, addr: u16, mode: AddressingMode) {
+        let base = match mode {
+            AddressingMode::AbsoluteY => addr,
+            AddressingMode::IndirectIndexed => self.read_zp_ptr(addr as u8),
+            _ => unreachable!(),
+        };
+
+        let val = self.regs[A as usize] & self.regs[X as usize];
+
+        self.store_high_and(base, Y, val);
+    }
+
+    pub fn shx(&mut self, addr: u16) {
+        let val = self.regs[X as usize];
+
+        self.store_high_and(addr, Y, val);
+    }
+
+    pub fn shy(&mut self, addr: u16) {
+        let val = self.regs[Y as usize];
+
+        self.store_high_and(addr, X, val);
+    }
+
+    pub fn slo(&mut self, addr: u16, mode: AddressingMode) {
+        let val = self.read_modify_write(addr, mode, Self::shl);
+
+        self.or(val);
+    }
+
+    pub fn sre(&mut self, addr: u16, mode: AddressingMode) {
+        let val = self.read_modify_write(addr, mode, Self::shr);
+
+        self.xor(val);
+    }
+
+    pub fn tas(&mut self, addr: u16) {
+        self.sp = self.regs[A as usize] & self.regs[X as usize];
+
+        self.store_high_and(addr, Y, self.sp);
+    }
+
+    pub fn xaa(&mut self, imm: u16) {
+        let val = (self.regs[A as usize] | MAGIC) & self.regs[X as usize] & imm as u8;
+
+        self.load(A, val);
+    }
+
+    /// Shared by SHA, SHX, SHY and TAS, which store `val` ANDed with the high byte of the base
+    /// address plus one. When indexing crosses a page, that value also replaces the high byte of
+    /// the target address.
+    fn store_high_and(&mut self, base: u16, idx: Register, val: u8) {
+        let offset = self.regs[idx as usize];
+        let target = base.wrapping_add(offset as u16);
+        let val = val & ((base >> 8) as u8).wrapping_add(1);
+
+        self.dummy_read((base & 0xFF00) | (target & 0xFF));
+
+        let target = if crosses_page(base, offset as i32) {
+            ((val as u16) << 8) | (target & 0xFF)
+        } else {
+            target
+        };
+
+        self.write(target, val);
+    }
+}
@@ -0,0 +1,142 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::video::PPU_WIDTH;
+
+mod scalers;
+
+/// Upscaler run on the CPU before a frame is handed to the window
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum Scaler {
+    /// No filtering, and only whole multiples of the frame's size on screen
+    #[default]
+    Nearest,
+    /// AdvMAME2x: doubles the size, rounding off diagonal edges between identical colors
+    Scale2x,
+    /// AdvMAME3x, the same at triple size
+    Scale3x,
+    /// Scale2x's edge rules on similar colors rather than identical ones, blending instead of
+    /// copying
+    Hq2x,
+    /// 2xBR, which weighs the edges in a 5x5 neighbourhood to smooth shallow slopes too
+    Xbr,
+    /// Doubles the size and darkens every other line like a CRT
+    Scanlines,
+}
+
+impl Scaler {
+    /// How many times larger the scaled frame is in each direction
+    pub fn factor(self) -> usize {
+        match self {
+            Scaler::Nearest => 1,
+            Scaler::Scale2x | Scaler::Hq2x | Scaler::Xbr | Scaler::Scanlines => 2,
+            Scaler::Scale3x => 3,
+        }
+    }
+
+    /// Scales the `width`x`height` frame in `src` into `dst`, which is resized to fit
+    pub fn scale(self, src: &[u32], width: usize, height: usize, dst: &mut Vec<u32>) {
+        let factor = self.factor();
+
+        dst.resize(width * factor * height * factor, 0);
+
+        match self {
+            Scaler::Nearest => dst.copy_from_slice(src),
+            Scaler::Scale2x => scalers::scale2x(src, width, height, dst),
+            Scaler::Scale3x => scalers::scale3x(src, width, height, dst),
+            Scaler::Hq2x => scalers::hq2x(src, width, height, dst),
+            Scaler::Xbr => scalers::xbr2x(src, width, height, dst),
+            Scaler::Scanlines => scalers::scanlines(src, width, height, dst),
+        }
+    }
+}
+
+impl Display for Scaler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Scaler::Nearest => write!(f, "nearest"),
+            Scaler::Scale2x => write!(f, "scale2x"),
+            Scaler::Scale3x => write!(f, "scale3x"),
+            Scaler::Hq2x => write!(f, "hq2x"),
+            Scaler::Xbr => write!(f, "xbr"),
+            Scaler::Scanlines => write!(f, "scanlines"),
+        }
+    }
+}
+
+impl FromStr for Scaler {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "nearest" => Ok(Scaler::Nearest),
+            "scale2x" => Ok(Scaler::Scale2x),
+            "scale3x" => Ok(Scaler::Scale3x),
+            "hq2x" => Ok(Scaler::Hq2x),
+            "xbr" => Ok(Scaler::Xbr),
+            "scanlines" => Ok(Scaler::Scanlines),
+            _ => Err(format!(
+                "unknown scaler `{s}`, expected nearest, scale2x, scale3x, hq2x, xbr or scanlines"
+            )),
+        }
+    }
+}
+
+/// How frames are cropped, scaled and fitted into the window
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DisplayOptions {
+    pub scaler: Scaler,
+    /// Shows pixels square instead of 8:7, as wide as they are on a TV
+    pub square_pixels: bool,
+    /// Hides the top and bottom 8 lines, which most TVs cut off
+    pub crop_vertical: bool,
+    /// Hides the leftmost 8 columns, where games often hide scrolling glitches
+    pub crop_left: bool,
+}
+
+impl DisplayOptions {
+    /// The size of the visible picture in NES pixels, after cropping
+    pub fn visible_size(&self) -> (usize, usize) {
+        let width = if self.crop_left { 248 } else { PPU_WIDTH };
+        let height = if self.crop_vertical { 224 } else { 240 };
+
+        (width, height)
+    }
+
+    /// The shape the picture should have on screen, as `(width, height)`
+    pub fn aspect_ratio(&self) -> (f64, f64) {
+        let (width, height) = self.visible_size();
+
+        if self.square_pixels {
+            (width as f64, height as f64)
+        } else {
+            (width as f64 * 8.0 / 7.0, height as f64)
+        }
+    }
+
+    /// Copies the visible part of a `width`x240 frame into `dst` and returns its size. Frames
+    /// wider than the PPU's (from the NTSC filter) are cropped proportionally.
+    pub fn crop(&self, src: &[u32], width: usize, dst: &mut Vec<u32>) -> (usize, usize) {
+        let left = if self.crop_left {
+            8 * width / PPU_WIDTH
+        } else {
+            0
+        };
+        let (top, bottom) = if self.crop_vertical {
+            (8, 232)
+        } else {
+            (0, 240)
+        };
+
+        dst.clear();
+
+        for line in src.chunks_exact(width).take(bottom).skip(top) {
+            dst.extend_from_slice(&line[left..]);
+        }
+
+        (width - left, bottom - top)
+    }
+}
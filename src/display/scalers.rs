@@ -0,0 +1,263 @@
+// Frames are RGBA with red in the lowest byte, as they come from the video stage
+
+/// Mixes colors by weight, channel by channel
+fn blend(colors: &[(u32, u32)]) -> u32 {
+    let total: u32 = colors.iter().map(|&(_, weight)| weight).sum();
+
+    (0..4).fold(0, |acc, channel| {
+        let shift = channel * 8;
+        let sum: u32 = colors
+            .iter()
+            .map(|&(color, weight)| ((color >> shift) & 0xFF) * weight)
+            .sum();
+
+        acc | (sum / total) << shift
+    })
+}
+
+fn yuv(color: u32) -> [i32; 3] {
+    let r = (color & 0xFF) as i32;
+    let g = ((color >> 8) & 0xFF) as i32;
+    let b = ((color >> 16) & 0xFF) as i32;
+
+    [
+        (299 * r + 587 * g + 114 * b) / 1000,
+        (-169 * r - 331 * g + 500 * b) / 1000,
+        (500 * r - 419 * g - 81 * b) / 1000,
+    ]
+}
+
+/// How different two colors look, weighing brightness the most
+fn distance([ay, au, av]: [i32; 3], [by, bu, bv]: [i32; 3]) -> i32 {
+    48 * (ay - by).abs() + 7 * (au - bu).abs() + 6 * (av - bv).abs()
+}
+
+/// hq2x's thresholds for colors that count as the same
+fn similar([ay, au, av]: [i32; 3], [by, bu, bv]: [i32; 3]) -> bool {
+    (ay - by).abs() <= 48 && (au - bu).abs() <= 7 && (av - bv).abs() <= 6
+}
+
+/// A source pixel and its neighbours, repeating the edge pixels outside the frame
+struct Window<'a> {
+    src: &'a [u32],
+    // YUV of every pixel of `src`, for the scalers that compare colors by similarity
+    yuv: &'a [[i32; 3]],
+    width: usize,
+    height: usize,
+    x: usize,
+    y: usize,
+}
+
+impl Window<'_> {
+    fn index(&self, dx: isize, dy: isize) -> usize {
+        let x = (self.x as isize + dx).clamp(0, self.width as isize - 1) as usize;
+        let y = (self.y as isize + dy).clamp(0, self.height as isize - 1) as usize;
+
+        x + y * self.width
+    }
+
+    fn at(&self, dx: isize, dy: isize) -> u32 {
+        self.src[self.index(dx, dy)]
+    }
+
+    fn yuv(&self, dx: isize, dy: isize) -> [i32; 3] {
+        self.yuv[self.index(dx, dy)]
+    }
+
+    /// Whether the pixel is surrounded by its own color, where there are no edges to smooth
+    fn is_flat(&self) -> bool {
+        let e = self.at(0, 0);
+
+        (-1..=1).all(|dy| (-1..=1).all(|dx| self.at(dx, dy) == e))
+    }
+}
+
+/// Runs `block` to fill the `factor`x`factor` output pixels of every source pixel
+fn for_each_block(
+    src: &[u32],
+    width: usize,
+    height: usize,
+    factor: usize,
+    with_yuv: bool,
+    dst: &mut [u32],
+    block: impl Fn(&Window, &mut [u32]),
+) {
+    let yuv: Vec<[i32; 3]> = if with_yuv {
+        src.iter().map(|&color| yuv(color)).collect()
+    } else {
+        Vec::new()
+    };
+
+    let mut out = vec![0; factor * factor];
+
+    for y in 0..height {
+        for x in 0..width {
+            let window = Window {
+                src,
+                yuv: &yuv,
+                width,
+                height,
+                x,
+                y,
+            };
+
+            block(&window, &mut out);
+
+            for (row, pixels) in out.chunks_exact(factor).enumerate() {
+                let start = (y * factor + row) * width * factor + x * factor;
+
+                dst[start..start + factor].copy_from_slice(pixels);
+            }
+        }
+    }
+}
+
+pub(super) fn scale2x(src: &[u32], width: usize, height: usize, dst: &mut [u32]) {
+    for_each_block(src, width, height, 2, false, dst, |window, out| {
+        let at = |dx, dy| window.at(dx, dy);
+        let (b, d, e, f, h) = (at(0, -1), at(-1, 0), at(0, 0), at(1, 0), at(0, 1));
+
+        if b != h && d != f {
+            out[0] = if d == b { d } else { e };
+            out[1] = if b == f { f } else { e };
+            out[2] = if d == h { d } else { e };
+            out[3] = if h == f { f } else { e };
+        } else {
+            out.fill(e);
+        }
+    });
+}
+
+pub(super) fn scale3x(src: &[u32], width: usize, height: usize, dst: &mut [u32]) {
+    for_each_block(src, width, height, 3, false, dst, |window, out| {
+        let at = |dx, dy| window.at(dx, dy);
+        let (a, b, c) = (at(-1, -1), at(0, -1), at(1, -1));
+        let (d, e, f) = (at(-1, 0), at(0, 0), at(1, 0));
+        let (g, h, i) = (at(-1, 1), at(0, 1), at(1, 1));
+
+        if b != h && d != f {
+            out[0] = if d == b { d } else { e };
+            out[1] = if (d == b && e != c) || (b == f && e != a) {
+                b
+            } else {
+                e
+            };
+            out[2] = if b == f { f } else { e };
+            out[3] = if (d == b && e != g) || (d == h && e != a) {
+                d
+            } else {
+                e
+            };
+            out[4] = e;
+            out[5] = if (b == f && e != i) || (h == f && e != c) {
+                f
+            } else {
+                e
+            };
+            out[6] = if d == h { d } else { e };
+            out[7] = if (d == h && e != i) || (h == f && e != g) {
+                h
+            } else {
+                e
+            };
+            out[8] = if h == f { f } else { e };
+        } else {
+            out.fill(e);
+        }
+    });
+}
+
+pub(super) fn hq2x(src: &[u32], width: usize, height: usize, dst: &mut [u32]) {
+    for_each_block(src, width, height, 2, true, dst, |window, out| {
+        let e = window.at(0, 0);
+
+        if window.is_flat() {
+            out.fill(e);
+
+            return;
+        }
+        let e_yuv = window.yuv(0, 0);
+
+        // Each corner looks at the two edge neighbours next to it and the ones across
+        for (corner, (dx, dy)) in [(-1, -1), (1, -1), (-1, 1), (1, 1)].into_iter().enumerate() {
+            let horiz = window.yuv(dx, 0);
+            let vert = window.yuv(0, dy);
+            let diagonal = window.yuv(dx, dy);
+
+            out[corner] = if similar(horiz, vert)
+                && !similar(horiz, e_yuv)
+                && !similar(vert, window.yuv(-dx, 0))
+                && !similar(horiz, window.yuv(0, -dy))
+            {
+                blend(&[(e, 2), (window.at(dx, 0), 1), (window.at(0, dy), 1)])
+            } else if !similar(diagonal, e_yuv) && (similar(horiz, e_yuv) || similar(vert, e_yuv)) {
+                blend(&[(e, 3), (window.at(dx, dy), 1)])
+            } else {
+                e
+            };
+        }
+    });
+}
+
+pub(super) fn xbr2x(src: &[u32], width: usize, height: usize, dst: &mut [u32]) {
+    for_each_block(src, width, height, 2, true, dst, |window, out| {
+        let e = window.at(0, 0);
+
+        if window.is_flat() {
+            out.fill(e);
+
+            return;
+        }
+
+        // The rules are written for the bottom right corner and mirrored for the others
+        for (corner, (sx, sy)) in [(-1, -1), (1, -1), (-1, 1), (1, 1)].into_iter().enumerate() {
+            let at = |dx: isize, dy: isize| window.yuv(dx * sx, dy * sy);
+
+            let (e_yuv, f, h) = (at(0, 0), at(1, 0), at(0, 1));
+
+            // Without an edge next to the corner, the blend below is a no-op
+            if f == e_yuv && h == e_yuv {
+                out[corner] = e;
+
+                continue;
+            }
+
+            let (b, c, d) = (at(0, -1), at(1, -1), at(-1, 0));
+            let (g, i) = (at(-1, 1), at(1, 1));
+            let (f4, i4, h5, i5) = (at(2, 0), at(2, 1), at(0, 2), at(1, 2));
+
+            // Weighted edge strength along each diagonal through the corner
+            let along = distance(e_yuv, c)
+                + distance(e_yuv, g)
+                + distance(i, h5)
+                + distance(i, f4)
+                + 4 * distance(h, f);
+            let across = distance(h, d)
+                + distance(h, i5)
+                + distance(f, i4)
+                + distance(f, b)
+                + 4 * distance(e_yuv, i);
+
+            out[corner] = if along < across {
+                let new = if distance(e_yuv, f) <= distance(e_yuv, h) {
+                    window.at(sx, 0)
+                } else {
+                    window.at(0, sy)
+                };
+
+                blend(&[(e, 1), (new, 1)])
+            } else {
+                e
+            };
+        }
+    });
+}
+
+pub(super) fn scanlines(src: &[u32], width: usize, height: usize, dst: &mut [u32]) {
+    for_each_block(src, width, height, 2, false, dst, |window, out| {
+        let e = window.at(0, 0);
+        let dark = blend(&[(e, 3), (e & 0xFF00_0000, 2)]);
+
+        out.copy_from_slice(&[e, e, dark, dark]);
+    });
+}
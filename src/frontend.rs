@@ -0,0 +1,340 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use sdl2::controller::{Axis, GameController};
+use sdl2::event::Event;
+use sdl2::EventPump;
+
+use keymap::{Keymap, SLOTS};
+use macros::Macros;
+
+use crate::bindings::{Action, Bindings};
+use crate::controller::Btn::*;
+use crate::controller::{Btn, Input};
+use crate::core::EmulatorCore;
+use crate::cpu::Cpu;
+use crate::region::Region;
+use crate::screen::Screen;
+use crate::speaker::Speaker;
+use crate::util::Config;
+use crate::State;
+
+mod keymap;
+mod macros;
+
+fn is_direction(button: Btn) -> bool {
+    matches!(button, Up | Down | Left | Right)
+}
+
+pub struct Nes {
+    dpad_in_use: [bool; 2],
+    event_queue: EventPump,
+    filename: String,
+    // Last frame the PPU finished, to tell when emulation moves on
+    frame: usize,
+    inputs: [Input; 2],
+    keymap: Keymap,
+    macros: Macros,
+    save_state_slot: usize,
+    save_states: Vec<Option<Box<Cpu>>>,
+    screen: Screen,
+    speaker: Speaker,
+    // Turbo buttons each player is holding
+    turbo: [Input; 2],
+    // The gamepads for players 1 and 2, in that order
+    controllers: Vec<GameController>,
+}
+
+impl Nes {
+    pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
+        let sdl_context = sdl2::init()?;
+
+        let controller_subsystem = sdl_context.game_controller()?;
+
+        let available = controller_subsystem.num_joysticks()?;
+
+        let controllers: Vec<GameController> = (0..available)
+            .filter(|&id| controller_subsystem.is_game_controller(id))
+            .filter_map(|id| controller_subsystem.open(id).ok())
+            .take(2)
+            .collect();
+
+        let bindings = match &config.bindings {
+            Some(path) => Bindings::load(path)
+                .map_err(|error| format!("Unable to read bindings file: {error}"))?,
+            None => Bindings::default(),
+        };
+
+        let filename = config.filename.clone();
+
+        Ok(Self {
+            dpad_in_use: [false; 2],
+            event_queue: sdl_context.event_pump()?,
+            frame: 0,
+            inputs: [Input::new(); 2],
+            keymap: Keymap::new(&bindings)?,
+            macros: Macros::default(),
+            save_states: Self::load_save_states(&filename),
+            filename,
+            save_state_slot: 0,
+            screen: Screen::new(&sdl_context, config.display)?,
+            speaker: Speaker::new(&sdl_context)?,
+            turbo: [Input::new(); 2],
+            controllers,
+        })
+    }
+
+    pub fn handle_input(&mut self, core: &mut EmulatorCore) {
+        let events: Vec<Event> = self.event_queue.poll_iter().collect();
+
+        for e in events {
+            match e {
+                Event::Quit { .. } => self.perform(Action::Quit, core),
+                Event::KeyDown {
+                    keycode: Some(keycode),
+                    repeat,
+                    ..
+                } => {
+                    if let (Some(&action), false) = (self.keymap.keys.get(&keycode), repeat) {
+                        self.perform(action, core);
+                    }
+
+                    for player in 0..2 {
+                        let keys = &self.keymap.players[player];
+
+                        if let Some(&button) = keys.turbo_keys.get(&keycode) {
+                            self.turbo[player].press(button);
+                        }
+
+                        if let Some(&button) = keys.keys.get(&keycode) {
+                            self.press(player, button);
+                        }
+                    }
+                }
+                Event::KeyUp {
+                    keycode: Some(keycode),
+                    ..
+                } => {
+                    for player in 0..2 {
+                        let keys = &self.keymap.players[player];
+
+                        if let Some(&button) = keys.keys.get(&keycode) {
+                            self.inputs[player].release(button);
+                        }
+
+                        if let Some(&button) = keys.turbo_keys.get(&keycode) {
+                            self.turbo[player].release(button);
+                        }
+                    }
+                }
+                Event::ControllerButtonDown { which, button, .. } => {
+                    if let Some(&action) = self.keymap.buttons.get(&button) {
+                        self.perform(action, core);
+                    }
+
+                    if let Some(player) = self.player(which) {
+                        let buttons = &self.keymap.players[player];
+
+                        if let Some(&turbo) = buttons.turbo_buttons.get(&button) {
+                            self.turbo[player].press(turbo);
+                        }
+
+                        if let Some(&button) = buttons.buttons.get(&button) {
+                            self.dpad_in_use[player] |= is_direction(button);
+                            self.press(player, button);
+                        }
+                    }
+                }
+                Event::ControllerButtonUp { which, button, .. } => {
+                    if let Some(player) = self.player(which) {
+                        let buttons = &self.keymap.players[player];
+
+                        if let Some(&turbo) = buttons.turbo_buttons.get(&button) {
+                            self.turbo[player].release(turbo);
+                        }
+
+                        if let Some(&button) = buttons.buttons.get(&button) {
+                            if is_direction(button) {
+                                self.dpad_in_use[player] = false;
+                            }
+
+                            self.inputs[player].release(button);
+                        }
+                    }
+                }
+                Event::ControllerAxisMotion {
+                    which, axis, value, ..
+                } => {
+                    if let Some(player) = self.player(which) {
+                        self.axis_motion(player, axis, value);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn perform(&mut self, action: Action, core: &mut EmulatorCore) {
+        match action {
+            Action::Quit => {
+                core.save_data();
+
+                core.request_termination = true;
+            }
+            Action::SaveState => {
+                let cpu = core.cpu.clone();
+
+                self.save_states[self.save_state_slot] = Some(cpu);
+            }
+            Action::LoadState => {
+                if let Some(cpu) = &self.save_states[self.save_state_slot] {
+                    core.load_state(cpu);
+                }
+            }
+            Action::SelectSlot(slot) => self.save_state_slot = slot,
+            Action::Pause => {
+                core.state = if core.state == State::Running {
+                    State::Halted
+                } else {
+                    State::Running
+                }
+            }
+            Action::FrameAdvance => core.state = State::StepFrame,
+            Action::Reset => core.cpu.reset(),
+            Action::Mute => self.speaker.muted = !self.speaker.muted,
+            Action::RecordMacro(slot) => self.macros.toggle_recording(slot),
+            Action::PlayMacro(slot) => self.macros.play(slot),
+        }
+    }
+
+    /// The player using the gamepad with the instance id `which`
+    fn player(&self, which: u32) -> Option<usize> {
+        self.controllers
+            .iter()
+            .position(|controller| controller.instance_id() == which)
+    }
+
+    /// Presses `button`, letting go of the opposite direction since the NES's DPad can't press
+    /// both
+    fn press(&mut self, player: usize, button: Btn) {
+        let controller = &mut self.inputs[player];
+
+        match button {
+            Up => controller.release(Down),
+            Down => controller.release(Up),
+            Left => controller.release(Right),
+            Right => controller.release(Left),
+            _ => {}
+        }
+
+        controller.press(button);
+    }
+
+    /// What the players are holding for the next frame, with turbo buttons pressed on every
+    /// other stretch of frames at their player's rate
+    fn held_inputs(&self, region: Region) -> [Input; 2] {
+        let mut inputs = self.inputs;
+
+        for (player, input) in inputs.iter_mut().enumerate() {
+            let rate = self.keymap.players[player].turbo_rate as f64;
+            let period = (region.frame_rate() / rate).round().max(2.0) as usize;
+
+            if self.frame % period < period / 2 {
+                input.combine(self.turbo[player]);
+            }
+        }
+
+        inputs
+    }
+
+    fn axis_motion(&mut self, player: usize, axis: Axis, value: i16) {
+        let deadzone = 5000;
+
+        if self.dpad_in_use[player] {
+            return;
+        }
+
+        if let Some(&[negative, positive]) = self.keymap.players[player].axes.get(&axis) {
+            let controller = &mut self.inputs[player];
+
+            controller.release(negative);
+            controller.release(positive);
+
+            if value < -deadzone {
+                controller.press(negative);
+            } else if value > deadzone {
+                controller.press(positive);
+            }
+        }
+    }
+
+    pub fn run(&mut self, core: &mut EmulatorCore) -> Result<(), Box<dyn Error>> {
+        let start = Instant::now();
+
+        self.handle_input(core);
+
+        let inputs = self.macros.overlay(self.held_inputs(core.region));
+        let output = core.run_frame(inputs)?;
+
+        if output.frame != self.frame {
+            self.frame = output.frame;
+            self.macros.advance(inputs);
+        }
+
+        for sample in output.audio_samples {
+            self.speaker.push_sample(sample)?;
+        }
+
+        self.speaker.flush()?;
+        self.screen.render(output.framebuffer, output.width)?;
+
+        let end = Instant::now();
+
+        core.update_fps(end.duration_since(start));
+
+        Ok(())
+    }
+
+    pub fn save_save_states(&self) {
+        if self.save_states.iter().all(|state| state.is_none()) {
+            return;
+        }
+
+        let mut name = Path::new(&self.filename).to_path_buf();
+
+        name.set_extension("stat");
+
+        let mut save_file = File::create(name).expect("Unable to open/create save state file.");
+
+        if let Ok(data) = serde_json::to_vec(&self.save_states) {
+            save_file
+                .write_all(data.as_slice())
+                .unwrap_or_else(|_| eprintln!("Unable to write save states to disk."));
+        }
+    }
+
+    fn load_save_states(filename: &str) -> Vec<Option<Box<Cpu>>> {
+        let mut name = Path::new(filename).to_path_buf();
+
+        name.set_extension("stat");
+
+        let mut states = vec![None; SLOTS];
+
+        if let Ok(mut save_file) = File::open(name) {
+            let mut data = Vec::new();
+
+            if save_file.read_to_end(&mut data).is_ok() {
+                states = serde_json::from_slice(&data)
+                    .map_err(|_| eprintln!("WARNING: Save state data invalid"))
+                    .unwrap_or(states);
+            } else {
+                eprintln!("WARNING: Failed to load save states from disk");
+            }
+        }
+
+        states
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::hash::Hash;
+
+use sdl2::controller::{Axis, Button};
+use sdl2::keyboard::Keycode;
+
+use crate::bindings::{Action, Bindings, Profile};
+use crate::controller::Btn;
+
+// Save state slots the frontend keeps
+pub(super) const SLOTS: usize = 9;
+
+/// A player's profile with the names resolved to SDL's keys, buttons and axes
+pub(super) struct PlayerMap {
+    pub keys: HashMap<Keycode, Btn>,
+    pub buttons: HashMap<Button, Btn>,
+    pub axes: HashMap<Axis, [Btn; 2]>,
+    pub turbo_keys: HashMap<Keycode, Btn>,
+    pub turbo_buttons: HashMap<Button, Btn>,
+    pub turbo_rate: u32,
+}
+
+/// The bindings file with the names resolved to SDL's keys, buttons and axes
+pub(super) struct Keymap {
+    pub keys: HashMap<Keycode, Action>,
+    pub buttons: HashMap<Button, Action>,
+    pub players: [PlayerMap; 2],
+}
+
+impl Keymap {
+    pub fn new(bindings: &Bindings) -> Result<Self, Box<dyn Error>> {
+        for action in bindings.keys.values().chain(bindings.buttons.values()) {
+            if let Action::SelectSlot(slot) = action {
+                if *slot >= SLOTS {
+                    return Err(format!("save state slot {slot} is out of range 0-8").into());
+                }
+            }
+        }
+
+        let [player1, player2] = &bindings.players;
+
+        Ok(Self {
+            keys: resolve(&bindings.keys, key)?,
+            buttons: resolve(&bindings.buttons, button)?,
+            players: [PlayerMap::new(player1)?, PlayerMap::new(player2)?],
+        })
+    }
+}
+
+impl PlayerMap {
+    fn new(profile: &Profile) -> Result<Self, Box<dyn Error>> {
+        if profile.turbo_rate == 0 {
+            return Err("turbo rate must be at least 1".into());
+        }
+
+        Ok(Self {
+            keys: resolve(&profile.keys, key)?,
+            buttons: resolve(&profile.buttons, button)?,
+            axes: resolve(&profile.axes, |name| {
+                Axis::from_string(name).ok_or_else(|| format!("unknown gamepad axis `{name}`"))
+            })?,
+            turbo_keys: resolve(&profile.turbo_keys, key)?,
+            turbo_buttons: resolve(&profile.turbo_buttons, button)?,
+            turbo_rate: profile.turbo_rate,
+        })
+    }
+}
+
+fn key(name: &str) -> Result<Keycode, String> {
+    Keycode::from_name(name).ok_or_else(|| format!("unknown key `{name}`"))
+}
+
+fn button(name: &str) -> Result<Button, String> {
+    Button::from_string(name).ok_or_else(|| format!("unknown gamepad button `{name}`"))
+}
+
+fn resolve<'a, K, V>(
+    names: impl IntoIterator<Item = (&'a String, &'a V)>,
+    lookup: impl Fn(&str) -> Result<K, String>,
+) -> Result<HashMap<K, V>, String>
+where
+    K: Hash + Eq,
+    V: Copy + 'a,
+{
+    names
+        .into_iter()
+        .map(|(name, &value)| Ok((lookup(name)?, value)))
+        .collect()
+}
@@ -0,0 +1,71 @@
+use std::collections::BTreeMap;
+
+use crate::controller::Input;
+
+/// Input macros: both players' buttons recorded frame by frame and played back on top of what
+/// they're pressing. Only frames that are actually emulated count, so pausing doesn't stretch
+/// a macro.
+#[derive(Default)]
+pub(super) struct Macros {
+    slots: BTreeMap<usize, Vec<[Input; 2]>>,
+    // Slot being recorded and what's been recorded so far
+    recording: Option<(usize, Vec<[Input; 2]>)>,
+    // Slot being played and the frame of it that's next
+    playing: Option<(usize, usize)>,
+}
+
+impl Macros {
+    /// Starts recording into `slot`, or stops recording and keeps what was recorded
+    pub fn toggle_recording(&mut self, slot: usize) {
+        match self.recording.take() {
+            Some((recorded_slot, frames)) => {
+                eprintln!("Recorded macro {recorded_slot} ({} frames)", frames.len());
+
+                if matches!(self.playing, Some((playing_slot, _)) if playing_slot == recorded_slot)
+                {
+                    self.playing = None;
+                }
+
+                self.slots.insert(recorded_slot, frames);
+            }
+            None => self.recording = Some((slot, Vec::new())),
+        }
+    }
+
+    /// Plays `slot` from the start, if it has been recorded
+    pub fn play(&mut self, slot: usize) {
+        if self
+            .slots
+            .get(&slot)
+            .is_some_and(|frames| !frames.is_empty())
+        {
+            self.playing = Some((slot, 0));
+        }
+    }
+
+    /// Adds the buttons of the macro being played to `inputs`
+    pub fn overlay(&self, mut inputs: [Input; 2]) -> [Input; 2] {
+        if let Some((slot, frame)) = self.playing {
+            for (input, played) in inputs.iter_mut().zip(self.slots[&slot][frame]) {
+                input.combine(played);
+            }
+        }
+
+        inputs
+    }
+
+    /// Moves on to the next frame after one has been emulated with `inputs`
+    pub fn advance(&mut self, inputs: [Input; 2]) {
+        if let Some((_, frames)) = &mut self.recording {
+            frames.push(inputs);
+        }
+
+        if let Some((slot, frame)) = &mut self.playing {
+            *frame += 1;
+
+            if *frame == self.slots[slot].len() {
+                self.playing = None;
+            }
+        }
+    }
+}
@@ -1,23 +1,33 @@
 use std::error::Error;
 use std::process;
+#[cfg(feature = "sdl")]
 use std::sync::{Arc, Mutex};
+#[cfg(feature = "sdl")]
 use std::thread;
+#[cfg(feature = "sdl")]
 use std::thread::sleep;
+#[cfg(feature = "sdl")]
 use std::time::Duration;
 
 use argh::FromArgs;
 
 use nes::core::EmulatorCore;
+use nes::display::Scaler;
+#[cfg(feature = "sdl")]
 use nes::gui::Gui;
+use nes::ntsc::{NtscFilter, NtscPreset};
+use nes::region::Region;
+use nes::regression::run_manifest;
+use nes::test_rom::{run_test_rom, DEFAULT_TIMEOUT_FRAMES};
 use nes::util::Config;
+#[cfg(feature = "sdl")]
 use nes::Nes;
-
-const CLOCKRATE: u32 = (1789773.0 / (60.0 / 60.0)) as u32;
+use nes::{NtscParams, PaletteSource};
 
 /// Emulator for the Nintendo Entertainment System
 #[derive(FromArgs)]
 struct Args {
-    /// nes ROM to load
+    /// nes ROM to load (or regression manifest with --regression)
     #[argh(positional)]
     filename: String,
     /// open debugger
@@ -26,24 +36,142 @@ struct Args {
     /// run CPU tests
     #[argh(switch, short = 't')]
     test: bool,
+    /// run a blargg-style test ROM headlessly and report its result
+    #[argh(switch)]
+    test_rom: bool,
+    /// frames to run a test ROM for before giving up
+    #[argh(option, default = "DEFAULT_TIMEOUT_FRAMES")]
+    timeout: usize,
+    /// CPU cycles to run a test ROM for before giving up
+    #[argh(option)]
+    timeout_cycles: Option<u64>,
+    /// run the framebuffer regression tests listed in a JSON manifest
+    #[argh(switch)]
+    regression: bool,
+    /// store the hashes produced by --regression as the new golden values
+    #[argh(switch)]
+    update_golden: bool,
+    /// palette file (.pal) with 64 or 512 colors to use instead of the built-in palette
+    #[argh(option)]
+    palette: Option<String>,
+    /// console region to emulate (ntsc, pal or dendy), instead of the one in the ROM header; the built-in palette stays NTSC
+    #[argh(option)]
+    region: Option<Region>,
+    /// NTSC signal filter to run the picture through (composite, svideo or rgb)
+    #[argh(option)]
+    filter: Option<NtscPreset>,
+    /// upscaler (nearest, scale2x, scale3x, hq2x, xbr or scanlines)
+    #[argh(option)]
+    scaler: Option<Scaler>,
+    /// show square pixels instead of the 8:7 aspect ratio of a TV
+    #[argh(switch)]
+    square_pixels: bool,
+    /// hide the top and bottom 8 lines
+    #[argh(switch)]
+    crop_vertical: bool,
+    /// hide the leftmost 8 columns
+    #[argh(switch)]
+    crop_left: bool,
+    /// draw every sprite on a scanline instead of at most 8
+    #[argh(switch)]
+    no_sprite_limit: bool,
+    /// bindings file (JSON) for keys, gamepad buttons and axes
+    #[argh(option)]
+    bindings: Option<String>,
+    /// JSON config file with default settings, which the other options override
+    #[argh(option)]
+    config: Option<String>,
+}
+
+fn load_core(config: &Config, test: bool) -> EmulatorCore {
+    let mut core = EmulatorCore::new(config, test).unwrap_or_else(|error| {
+        eprintln!("Error: {error}");
+
+        process::exit(1);
+    });
+
+    if let Some(palette) = &config.palette {
+        core.set_palette(PaletteSource::File(palette.clone()))
+            .unwrap_or_else(|error| {
+                eprintln!("Error: {error}");
+
+                process::exit(1);
+            });
+    }
+
+    if let Some(preset) = config.filter {
+        core.set_video_filter(Box::new(NtscFilter::new(preset, NtscParams::default())));
+    }
+
+    core
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     let args: Args = argh::from_env();
-    let config = Config::new(&args.filename);
+    let mut config = match &args.config {
+        Some(path) => Config::from_file(path, &args.filename).unwrap_or_else(|error| {
+            eprintln!("Error: Unable to read config file: {error}");
+
+            process::exit(1);
+        }),
+        None => Config::new(&args.filename),
+    };
+
+    config.region = args.region.or(config.region);
+    config.palette = args.palette.or(config.palette);
+    config.filter = args.filter.or(config.filter);
+    config.display.scaler = args.scaler.unwrap_or(config.display.scaler);
+    config.display.square_pixels |= args.square_pixels;
+    config.display.crop_vertical |= args.crop_vertical;
+    config.display.crop_left |= args.crop_left;
+    config.no_sprite_limit |= args.no_sprite_limit;
+    config.bindings = args.bindings.or(config.bindings);
 
     if args.test {
-        let mut core = EmulatorCore::new(&config, CLOCKRATE, true)?;
+        let mut core = load_core(&config, true);
 
         core.cpu.test()?;
 
         return Ok(());
     }
 
-    if !args.debug {
-        let mut core = EmulatorCore::new(&config, CLOCKRATE, false)?;
+    if args.regression {
+        let report = run_manifest(&args.filename, args.update_golden)?;
+
+        println!("{report}");
+
+        process::exit(if report.passed() || args.update_golden {
+            0
+        } else {
+            1
+        });
+    }
+
+    if args.test_rom {
+        config.no_battery = true;
+
+        let mut core = load_core(&config, false);
+
+        core.cpu.reset();
+
+        let report = run_test_rom(&mut core, args.timeout, args.timeout_cycles)?;
+
+        println!("{}: {report}", args.filename);
+
+        process::exit(if report.passed() { 0 } else { 1 });
+    }
+
+    run_frontend(&config, args.debug);
+
+    Ok(())
+}
+
+#[cfg(feature = "sdl")]
+fn run_frontend(config: &Config, debug: bool) {
+    if !debug {
+        let mut core = load_core(config, false);
 
-        let mut nes = Nes::new(CLOCKRATE, &config).unwrap_or_else(|error| {
+        let mut nes = Nes::new(config).unwrap_or_else(|error| {
             eprintln!("Error: {error}");
 
             process::exit(1);
@@ -60,13 +188,14 @@ fn main() -> Result<(), Box<dyn Error>> {
             nes.run(&mut core).unwrap();
         }
     } else {
-        let core = Arc::new(Mutex::new(EmulatorCore::new(&config, CLOCKRATE, false)?));
+        let core = Arc::new(Mutex::new(load_core(config, false)));
         let gui = Gui::new(core.clone());
+        let config = config.clone();
 
         thread::Builder::new()
             .stack_size(8 * 1024 * 1024)
             .spawn(move || {
-                let mut nes = Nes::new(CLOCKRATE, &config).unwrap_or_else(|error| {
+                let mut nes = Nes::new(&config).unwrap_or_else(|error| {
                     eprintln!("Error: {error}");
 
                     process::exit(1);
@@ -93,6 +222,13 @@ fn main() -> Result<(), Box<dyn Error>> {
 
         eframe::run_native("GUI", options, Box::new(|_cc| Box::new(gui)));
     }
+}
 
-    Ok(())
+#[cfg(not(feature = "sdl"))]
+fn run_frontend(_config: &Config, _debug: bool) {
+    eprintln!(
+        "Error: nes was built without the `sdl` feature, so only headless modes are available"
+    );
+
+    process::exit(1);
 }
@@ -0,0 +1,300 @@
+use std::f32::consts::PI;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ppu::{NtscParams, Rgba};
+use crate::video::VideoFilter;
+
+/// Width of the filtered picture. The composite signal has 8 samples per PPU pixel, which are
+/// decoded to 2.5 output pixels each.
+pub const OUTPUT_WIDTH: usize = 640;
+
+const SAMPLES_PER_PIXEL: usize = 8;
+const LINE_SAMPLES: usize = 256 * SAMPLES_PER_PIXEL;
+
+// Voltages of the four luma levels, for the low and high half of the color wave
+const LEVELS: [f32; 8] = [0.350, 0.518, 0.962, 1.550, 1.094, 1.506, 1.962, 1.962];
+const BLACK: f32 = 0.518;
+const WHITE: f32 = 1.962;
+// Hues are decoded relative to the colorburst, which has the phase of color $x8
+const COLORBURST: f32 = 8.0;
+// Resolution of the filter's gamma lookup table
+const GAMMA_STEPS: usize = 1024;
+
+/// How much the emphasis bits darken the colors they don't emphasize
+pub(crate) const ATTENUATION: f32 = 0.746;
+
+/// The voltage the PPU outputs for `pixel` (a color index with the emphasis bits above it) at
+/// `phase` of its 12 phase color clock
+pub(crate) fn signal(pixel: u16, phase: usize) -> f32 {
+    let color = (pixel & 0xF) as usize;
+    let level = ((pixel >> 4) & 3) as usize;
+    let emphasis = pixel >> 6;
+
+    let (lo, hi) = if color > 0xD {
+        (BLACK, BLACK)
+    } else {
+        (
+            LEVELS[level + 4 * (color == 0x0) as usize],
+            LEVELS[level + 4 * (color < 0xD) as usize],
+        )
+    };
+
+    let in_color_phase = |color: usize| (color + phase) % 12 < 6;
+
+    let spot = if in_color_phase(color) { hi } else { lo };
+
+    if (emphasis & 1 != 0 && in_color_phase(0))
+        || (emphasis & 2 != 0 && in_color_phase(4))
+        || (emphasis & 4 != 0 && in_color_phase(8))
+    {
+        spot * ATTENUATION
+    } else {
+        spot
+    }
+}
+
+/// Scales a voltage from the signal to 0.0 for black and 1.0 for white
+pub(crate) fn level(voltage: f32, params: &NtscParams) -> f32 {
+    let v = (voltage - BLACK) / (WHITE - BLACK);
+
+    ((v - 0.5) * params.contrast + 0.5) * params.brightness
+}
+
+/// The angle `phase` is demodulated at, which is where hue comes from
+pub(crate) fn hue_angle(phase: usize, params: &NtscParams) -> f32 {
+    PI / 6.0 * (phase as f32 - COLORBURST + params.hue / 30.0)
+}
+
+pub(crate) fn yiq_to_rgba(y: f32, i: f32, q: f32, params: &NtscParams) -> Rgba {
+    let i = i * params.saturation;
+    let q = q * params.saturation;
+
+    let gamma_fix = |f: f32| {
+        let f = if f <= 0.0 {
+            0.0
+        } else {
+            f.powf(2.2 / params.gamma)
+        };
+
+        (f * 255.95).clamp(0.0, 255.0) as u8
+    };
+
+    let r = gamma_fix(y + 0.946882 * i + 0.623557 * q);
+    let g = gamma_fix(y - 0.274788 * i - 0.635691 * q);
+    let b = gamma_fix(y - 1.108545 * i + 1.709007 * q);
+
+    Rgba(r, g, b, 255)
+}
+
+/// The kind of cable between the console and the TV
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum NtscPreset {
+    /// Luma and chroma share one signal, so sharp color changes bleed into each other and
+    /// crawl from frame to frame
+    #[default]
+    Composite,
+    /// Separate luma and chroma: sharp brightness, but blurry color
+    SVideo,
+    /// Every pixel decoded on its own, with no blending at all
+    Rgb,
+}
+
+impl Display for NtscPreset {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NtscPreset::Composite => write!(f, "composite"),
+            NtscPreset::SVideo => write!(f, "svideo"),
+            NtscPreset::Rgb => write!(f, "rgb"),
+        }
+    }
+}
+
+impl FromStr for NtscPreset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "composite" => Ok(NtscPreset::Composite),
+            "svideo" | "s-video" => Ok(NtscPreset::SVideo),
+            "rgb" => Ok(NtscPreset::Rgb),
+            _ => Err(format!(
+                "unknown filter `{s}`, expected composite, svideo or rgb"
+            )),
+        }
+    }
+}
+
+/// Simulates a TV decoding the PPU's composite video output. Works from raw pixels (color index
+/// and emphasis bits) rather than RGB, as the artifacts depend on the signal the PPU generates
+/// for them.
+pub struct NtscFilter {
+    preset: NtscPreset,
+    params: NtscParams,
+    output: Vec<u32>,
+    // Running sums of the luma, I and Q parts of a line, so any window is two lookups
+    sums: [Vec<f32>; 3],
+    // Lookup tables, as the signal only depends on the pixel and the phase
+    levels: Vec<[f32; 12]>,
+    lumas: Vec<f32>,
+    carrier: [(f32, f32); 12],
+    colors: Vec<u32>,
+    gamma: Vec<u8>,
+}
+
+impl NtscFilter {
+    pub fn new(preset: NtscPreset, params: NtscParams) -> Self {
+        let levels: Vec<[f32; 12]> = (0..512)
+            .map(|pixel| std::array::from_fn(|phase| level(signal(pixel, phase), &params)))
+            .collect();
+
+        let carrier = std::array::from_fn(|phase| {
+            let angle = hue_angle(phase, &params);
+
+            (angle.cos(), angle.sin())
+        });
+
+        // What each pixel decodes to on its own, which is the palette the filter is based on
+        let colors = levels
+            .iter()
+            .map(|levels| {
+                let (mut y, mut i, mut q) = (0.0, 0.0, 0.0);
+
+                for (v, (cos, sin)) in levels.iter().zip(carrier) {
+                    y += v / 12.0;
+                    i += v * cos / 12.0;
+                    q += v * sin / 12.0;
+                }
+
+                yiq_to_rgba(y, i, q, &params).into()
+            })
+            .collect();
+
+        let lumas = levels
+            .iter()
+            .map(|levels| levels.iter().sum::<f32>() / 12.0)
+            .collect();
+
+        let gamma = (0..GAMMA_STEPS)
+            .map(|step| {
+                let f = step as f32 / (GAMMA_STEPS - 1) as f32;
+
+                (f.powf(2.2 / params.gamma) * 255.95).clamp(0.0, 255.0) as u8
+            })
+            .collect();
+
+        Self {
+            preset,
+            params,
+            output: vec![0; OUTPUT_WIDTH * 240],
+            sums: [
+                vec![0.0; LINE_SAMPLES + 1],
+                vec![0.0; LINE_SAMPLES + 1],
+                vec![0.0; LINE_SAMPLES + 1],
+            ],
+            levels,
+            lumas,
+            carrier,
+            colors,
+            gamma,
+        }
+    }
+
+    /// Generates the signal for a line and accumulates it. S-Video splits off the luma of each
+    /// pixel first, so only the chroma part is demodulated.
+    fn fill_sums(&mut self, pixels: &[u16], line_phase: usize) {
+        let [y_sums, i_sums, q_sums] = &mut self.sums;
+
+        let (mut y, mut i, mut q) = (0.0, 0.0, 0.0);
+
+        for (sample, &pixel) in pixels
+            .iter()
+            .flat_map(|pixel| [pixel; SAMPLES_PER_PIXEL])
+            .enumerate()
+        {
+            let pixel = pixel as usize & 0x1FF;
+            let phase = (line_phase + sample) % 12;
+
+            let v = self.levels[pixel][phase];
+            let (cos, sin) = self.carrier[phase];
+
+            y += match self.preset {
+                NtscPreset::SVideo => self.lumas[pixel],
+                _ => v,
+            };
+            i += v * cos;
+            q += v * sin;
+
+            y_sums[sample + 1] = y;
+            i_sums[sample + 1] = i;
+            q_sums[sample + 1] = q;
+        }
+    }
+}
+
+impl VideoFilter for NtscFilter {
+    fn width(&self) -> usize {
+        OUTPUT_WIDTH
+    }
+
+    /// The phase moves from frame to frame, which makes the artifacts crawl
+    fn apply(&mut self, pixels: &[u16], phase: u8) -> &[u32] {
+        // Luma and chroma are averaged over one color cycle (or two for the lower chroma
+        // bandwidth), except for S-Video, which carries luma separately at full bandwidth
+        let (luma_window, chroma_window) = match self.preset {
+            NtscPreset::Composite => (12, 24),
+            NtscPreset::SVideo => (4, 24),
+            NtscPreset::Rgb => (0, 0),
+        };
+
+        for (line, pixels) in pixels.chunks_exact(256).enumerate() {
+            let range = line * OUTPUT_WIDTH..(line + 1) * OUTPUT_WIDTH;
+
+            if self.preset == NtscPreset::Rgb {
+                for (x, rgba) in self.output[range].iter_mut().enumerate() {
+                    *rgba = self.colors[pixels[x * 256 / OUTPUT_WIDTH] as usize & 0x1FF];
+                }
+
+                continue;
+            }
+
+            // A line is 341 dots of 8 samples, which leaves the next line 4 phases further
+            let line_phase = phase as usize + line * 4;
+
+            self.fill_sums(pixels, line_phase);
+
+            let [y_sums, i_sums, q_sums] = &self.sums;
+
+            let window = |sums: &Vec<f32>, center: usize, width: usize| {
+                let start = center.saturating_sub(width / 2);
+                let end = (center + width / 2).min(LINE_SAMPLES);
+
+                (sums[end] - sums[start]) / (end - start) as f32
+            };
+
+            let gamma_fix = |f: f32| {
+                let step = (f * (GAMMA_STEPS - 1) as f32).clamp(0.0, (GAMMA_STEPS - 1) as f32);
+
+                self.gamma[step as usize]
+            };
+
+            for (x, rgba) in self.output[range].iter_mut().enumerate() {
+                let center = x * LINE_SAMPLES / OUTPUT_WIDTH;
+
+                let y = window(y_sums, center, luma_window);
+                let i = window(i_sums, center, chroma_window) * self.params.saturation;
+                let q = window(q_sums, center, chroma_window) * self.params.saturation;
+
+                let r = gamma_fix(y + 0.946882 * i + 0.623557 * q);
+                let g = gamma_fix(y - 0.274788 * i - 0.635691 * q);
+                let b = gamma_fix(y - 1.108545 * i + 1.709007 * q);
+
+                *rgba = Rgba(r, g, b, 255).into();
+            }
+        }
+
+        &self.output
+    }
+}
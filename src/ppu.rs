@@ -7,13 +7,15 @@ use address::Address;
 use ctrl::Control;
 use mask::Mask;
 use oam::{Oam, Sprite};
-pub use palette::{PaletteTable, Rgba};
+pub use palette::{NtscParams, PaletteError, PaletteSource, PaletteTable, Rgba};
 use status::Status;
 
-use crate::bus::mapper::BankSettings;
+use crate::bus::irq::IrqLine;
+use crate::bus::mapper::{BankSettings, Nametable};
 use crate::cartridge::{Cartridge, MapperType, Mirroring};
 use crate::ppu::mmc3::Mmc3;
 use crate::ppu::oam::Attributes;
+use crate::region::Region;
 use crate::util::bit::Bit;
 use crate::util::shift_reg::ShiftRegister;
 use debug::Menu;
@@ -27,23 +29,36 @@ mod oam;
 mod palette;
 mod status;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Nmi;
+fn blank_pixels() -> [u16; 256 * 240] {
+    // Color $0F is black, which is what the overscan area shows
+    [0x0F; 256 * 240]
+}
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Ppu {
     pub bank_settings: BankSettings,
     pub chr: Vec<u8>,
     pub dot: u16,
-    #[serde(with = "BigArray")]
-    pub fb: [u32; 256 * 240],
     pub frame: usize,
-    pub interrupts: Vec<Nmi>,
     pub mirroring: Mirroring,
     pub mmc3: Option<Mmc3>,
-    pub mmc7_vram_page: u8,
+    /// Where each nametable slot at $2000-$2FFF is mapped, as set by the mirroring or the mapper.
+    /// Save states leave it out and the mapper maps it again after loading one.
+    #[serde(skip)]
+    nametables: [Nametable; 4],
     pub nmi_occurred: Cell<bool>,
+    /// Draws every sprite on a scanline instead of the first 8. Evaluation, and with it the
+    /// overflow flag, still works as on hardware.
+    #[serde(default)]
+    pub no_sprite_limit: bool,
+    /// Color clock phase the current frame's signal started on, for the NTSC filter
+    #[serde(default)]
+    pub ntsc_phase: u8,
     pub oam: Oam,
+    /// The picture, as the color index of every pixel with the emphasis bits above it. Turning
+    /// it into RGBA is up to the frontend's video stage.
+    #[serde(with = "BigArray", default = "blank_pixels")]
+    pub pixels: [u16; 256 * 240],
     pub ram: bool,
     pub scanline: u16,
     a12: Cell<bool>,
@@ -57,18 +72,26 @@ pub struct Ppu {
     bus: Cell<u8>,
     ctrl: Control,
     cur_spr: Option<Sprite>,
-    last_nmi_pair: bool,
+    // Pattern rows of the sprites past the first 8, with no sprite limit
+    #[serde(default)]
+    extra_sprites: Vec<(Sprite, u8, u8)>,
     mapper_type: MapperType,
     mask: Mask,
+    // Nametable RAM on the cartridge, for `Nametable::CartRam`
+    #[serde(default)]
+    nametable_ram: Vec<u8>,
     nt_latch: u8,
     odd: bool,
     old_a12: Cell<bool>,
+    // Colors for the debugger's views
     palette: PaletteTable,
     ppuscroll_delay: (u8, bool),
     pram: [u8; 0x20],
     pt_hi_latch: u8,
     pt_lo_latch: u8,
     pt_shifters: [ShiftRegister<u16, 16>; 2],
+    #[serde(default)]
+    region: Region,
     #[serde(skip)]
     selected_menu: Cell<Menu>,
     spr0_present: bool,
@@ -89,8 +112,10 @@ pub struct Ppu {
 }
 
 impl Ppu {
-    pub fn new(mut cartridge: Cartridge, mapper_type: MapperType) -> Self {
+    pub fn new(mut cartridge: Cartridge, mapper_type: MapperType, region: Region) -> Self {
         let mirroring = cartridge.mirroring;
+        let chr_ram_size = cartridge.total_chr_ram_size().max(0x2000);
+        let nametable_ram_size = cartridge.nametable_ram_size();
         let chr_rom = cartridge.chr_rom.take().unwrap();
         let bank_settings = BankSettings::new(match mapper_type {
             MapperType::Nrom => {
@@ -124,25 +149,26 @@ impl Ppu {
         Self {
             bank_settings,
             chr: if chr_rom.is_empty() {
-                vec![0; 0x2000]
+                vec![0; chr_ram_size]
             } else {
                 chr_rom
             },
             dot: 0,
-            fb: [0; 256 * 240],
             frame: 1,
-            interrupts: Vec::new(),
             mirroring,
             mmc3: if mapper_type == MapperType::MMC3 {
                 Some(Mmc3::new())
             } else {
                 None
             },
-            mmc7_vram_page: 0,
+            nametables: Nametable::mirrored(mirroring),
             nmi_occurred: Cell::new(false),
+            no_sprite_limit: false,
+            ntsc_phase: 0,
             oam: Oam::new(),
+            pixels: blank_pixels(),
             ram,
-            scanline: 261,
+            scanline: region.scanlines() - 1,
             a12: Cell::new(false),
             a12_filter: Cell::new(9),
             a12_rising: Cell::new(false),
@@ -154,9 +180,10 @@ impl Ppu {
             bus: Cell::new(0),
             ctrl: Control::new(),
             cur_spr: None,
-            last_nmi_pair: false,
+            extra_sprites: Vec::new(),
             mapper_type,
             mask: Mask::new(),
+            nametable_ram: vec![0; nametable_ram_size],
             nt_latch: 0,
             odd: false,
             old_a12: Cell::new(false),
@@ -166,6 +193,7 @@ impl Ppu {
             pt_hi_latch: 0,
             pt_lo_latch: 0,
             pt_shifters: [ShiftRegister::new(); 2],
+            region,
             selected_menu: Cell::new(Menu::Registers),
             spr0_present: true,
             spr_active: [false; 8],
@@ -184,6 +212,11 @@ impl Ppu {
         }
     }
 
+    /// Level of the PPU's /NMI output, the CPU triggers on its rising edge
+    pub fn nmi_line(&self) -> bool {
+        self.ctrl.nmi && self.nmi_occurred.get()
+    }
+
     pub fn read_reg(&self, reg: u8) -> u8 {
         match reg {
             0 => self.bus.get(),
@@ -208,9 +241,13 @@ impl Ppu {
             }
             3 => self.bus.get(),
             4 => {
-                let blank = self.status.get().vblank
-                    || (!self.mask.show_background && !self.mask.show_sprites);
-                let data = self.oam.read(!blank);
+                let rendering = (self.mask.show_background || self.mask.show_sprites)
+                    && (self.scanline < 240 || self.scanline == self.region.scanlines() - 1);
+                let data = if rendering {
+                    self.oam.read_while_rendering(self.dot)
+                } else {
+                    self.oam.read(true)
+                };
 
                 self.bus.replace(data);
 
@@ -242,28 +279,47 @@ impl Ppu {
         }
     }
 
+    pub fn set_palette(&mut self, palette: PaletteTable) {
+        self.palette = palette;
+    }
+
     pub fn set_mirroring_mode(&mut self, mode: Mirroring) {
         self.mirroring = mode;
+        self.set_nametables(Nametable::mirrored(mode));
     }
 
-    pub fn tick(&mut self) {
+    /// Maps the four nametable slots. Pages of cartridge nametable RAM past what the board was
+    /// given are added, so any mapper can use it.
+    pub fn set_nametables(&mut self, slots: [Nametable; 4]) {
+        for slot in slots {
+            if let Nametable::CartRam(page) = slot {
+                let size = (page + 1) * 0x400;
+
+                if self.nametable_ram.len() < size {
+                    self.nametable_ram.resize(size, 0);
+                }
+            }
+        }
+
+        self.nametables = slots;
+    }
+
+    pub fn tick(&mut self, irq: &IrqLine) {
         let show_bg = self.mask.show_background;
         let show_spr = self.mask.show_sprites;
         let rendering = show_bg || show_spr;
 
+        let scanlines = self.region.scanlines();
+
         // Idle on first dot
         if self.dot != 0 {
             match self.scanline {
                 0..=239 => self.tick_visible_scanline(rendering),
-                241 if self.dot == 1 => {
+                line if line == self.region.vblank_scanline() && self.dot == 1 => {
                     self.status.get_mut().vblank = true;
-
-                    if self.ctrl.nmi {
-                        self.nmi_occurred.replace(true);
-                        self.interrupts.push(Nmi {});
-                    }
+                    self.nmi_occurred.replace(true);
                 }
-                261 => self.tick_prerender_scanline(rendering),
+                line if line == scanlines - 1 => self.tick_prerender_scanline(rendering),
                 _ => {}
             }
         }
@@ -271,7 +327,7 @@ impl Ppu {
         self.dot = (self.dot + 1) % 341;
 
         if self.dot == 0 {
-            self.scanline = (self.scanline + 1) % 262;
+            self.scanline = (self.scanline + 1) % scanlines;
             self.sx = 0;
         }
 
@@ -300,7 +356,7 @@ impl Ppu {
             if a12_rising {
                 self.a12_rising.replace(false);
 
-                mmc3.irq.clock();
+                mmc3.irq.clock(irq);
             }
         }
     }
@@ -317,13 +373,6 @@ impl Ppu {
                 let addr = addr.bits_abs(12, 14) | addr.bits_abs(0, 9) | (nt_bits << 10);
 
                 self.t.update(addr);
-
-                let nmi_pair = self.ctrl.nmi && self.nmi_occurred.get();
-
-                if !self.last_nmi_pair && nmi_pair {
-                    self.last_nmi_pair = nmi_pair;
-                    self.interrupts.push(Nmi {});
-                }
             }
             1 => {
                 self.mask = Mask::from(data);
@@ -411,7 +460,8 @@ impl Ppu {
                 let pixel = 1 << 4 | (attrib << 2) | palette_idx;
 
                 Some((idx, pixel, priority))
-            });
+            })
+            .or_else(|| self.extra_spr_pixel());
 
         self.spr_active
             .iter_mut()
@@ -433,6 +483,57 @@ impl Ppu {
         pixel
     }
 
+    /// The pixel of the first sprite past the first 8 that has one at this dot
+    fn extra_spr_pixel(&self) -> Option<(usize, u8, bool)> {
+        self.extra_sprites
+            .iter()
+            .find_map(|&(sprite, pt_low, pt_high)| {
+                let column = self.sx.checked_sub(sprite.x as u16).filter(|&x| x < 8)?;
+                let shift = 7 - column;
+                let palette_idx = (pt_high >> shift & 1) << 1 | (pt_low >> shift & 1);
+
+                if palette_idx == 0 {
+                    return None;
+                }
+
+                let pixel = 1 << 4 | (sprite.attrib.palette << 2) | palette_idx;
+
+                // Never sprite 0, so it can't cause a sprite 0 hit
+                Some((8, pixel, sprite.attrib.priority))
+            })
+    }
+
+    /// Reads the pattern rows of the sprites past the first 8. There's no such fetch on
+    /// hardware, so it doesn't show up on the address bus for mappers to see.
+    fn fetch_extra_sprites(&mut self) {
+        let spr_height = (self.ctrl.size / 8) as u16;
+
+        self.extra_sprites = self
+            .oam
+            .extra_sprites
+            .iter()
+            .map(|&sprite| {
+                let row = self.scanline - sprite.y as u16;
+                let row = if sprite.attrib.flip_vert {
+                    (spr_height - 1) - row
+                } else {
+                    row
+                };
+
+                let (pt, tile_idx, offset) = self.nt_byte_to_pt_addr(sprite.tile_idx, row);
+                let addr = pt + tile_idx as u16 * 16 + offset;
+
+                let (pt_low, pt_high) = (self.read(addr), self.read(addr + 8));
+
+                if sprite.attrib.flip_horiz {
+                    (sprite, pt_low.reverse_bits(), pt_high.reverse_bits())
+                } else {
+                    (sprite, pt_low, pt_high)
+                }
+            })
+            .collect();
+    }
+
     fn calculate_bg_pixel(&self) -> u8 {
         if self.sx < 8 && !self.mask.show_background_leftmost_8 {
             return 0;
@@ -459,12 +560,6 @@ impl Ppu {
         }
     }
 
-    fn draw_pixel(&mut self, rgb: Option<Rgba>, x: usize, y: usize) {
-        let idx = x + y * 256;
-
-        self.fb[idx] = rgb.map_or(0, <u32>::from);
-    }
-
     fn fetch_at_byte(&self) -> u8 {
         let v = self.v.address();
         let addr = 0x23C0 | (v & 0xC00) | ((v >> 4) & 0x38) | ((v >> 2) & 0x7);
@@ -501,60 +596,35 @@ impl Ppu {
         self.read(addr)
     }
 
-    fn mirrored_address(&self, addr: u16) -> u16 {
-        let addr = match addr {
-            0x3000..=0x3EFF => addr - 0x1000,
-            0x0..=0x1FFF => return addr,
-            _ => addr,
-        };
-
-        match self.mirroring {
-            Mirroring::Vertical => {
-                let addr = if addr >= 0x2800 { addr - 0x800 } else { addr };
-
-                addr - 0x2000
-            }
-            Mirroring::Horizontal => {
-                let addr = match addr {
-                    0x2400..=0x27FF => addr - 0x400,
-                    0x2800..=0x2BFF => addr - 0x400,
-                    0x2C00..=0x2FFF => addr - 0x800,
-                    _ => addr,
-                };
-
-                addr - 0x2000
-            }
-            Mirroring::OneScreenLowerBank => {
-                let addr = match addr {
-                    0x2400..=0x27FF => addr - 0x400,
-                    0x2800..=0x2BFF => addr - 0x800,
-                    0x2C00..=0x2FFF => addr - 0xC00,
-                    _ => addr,
-                };
+    /// The slot a nametable address falls in and the offset within it
+    fn nametable_slot(&self, addr: u16) -> (Nametable, usize) {
+        let addr = (addr as usize - 0x2000) % 0x1000;
 
-                addr - 0x2000
-            }
-            Mirroring::OneScreenUpperBank => {
-                let addr = match addr {
-                    0x2000..=0x23FF => addr + 0x400,
-                    0x2800..=0x2BFF => addr - 0x400,
-                    0x2C00..=0x2FFF => addr - 0x800,
-                    _ => addr,
-                };
+        (self.nametables[addr / 0x400], addr % 0x400)
+    }
 
-                addr - 0x2000
+    fn read_nametable(&self, addr: u16) -> u8 {
+        match self.nametable_slot(addr) {
+            (Nametable::CiramA, offset) => self.vram[offset],
+            (Nametable::CiramB, offset) => self.vram[0x400 + offset],
+            (Nametable::CartRam(page), offset) => self.nametable_ram[page * 0x400 + offset],
+            (Nametable::ChrRom(page), offset) => self.chr[(page * 0x400 + offset) % self.chr.len()],
+            (Nametable::Fill { tile, attribute }, offset) => {
+                if offset < 0x3C0 {
+                    tile
+                } else {
+                    attribute
+                }
             }
-            Mirroring::FourScreen => addr - 0x2000,
-            Mirroring::SingleScreen => {
-                let addr = match addr {
-                    0x2400..=0x27FF => addr - 0x400,
-                    0x2800..=0x2BFF => addr - 0x800,
-                    0x2C00..=0x2FFF => addr - 0xC00,
-                    _ => addr,
-                };
+        }
+    }
 
-                addr - 0x2000 + (self.mmc7_vram_page as u16 * 0x400)
-            }
+    fn write_nametable(&mut self, addr: u16, data: u8) {
+        match self.nametable_slot(addr) {
+            (Nametable::CiramA, offset) => self.vram[offset] = data,
+            (Nametable::CiramB, offset) => self.vram[0x400 + offset] = data,
+            (Nametable::CartRam(page), offset) => self.nametable_ram[page * 0x400 + offset] = data,
+            (Nametable::ChrRom(_) | Nametable::Fill { .. }, _) => {}
         }
     }
 
@@ -616,11 +686,7 @@ impl Ppu {
                     self.write_chr(addr, data);
                 }
             }
-            0x2000..=0x3EFF => {
-                let addr = self.mirrored_address(addr);
-
-                self.vram[addr as usize] = data;
-            }
+            0x2000..=0x3EFF => self.write_nametable(addr, data),
             0x3F00..=0x3FFF => {
                 let addr = (addr % 0x20) as usize;
 
@@ -640,11 +706,7 @@ impl Ppu {
 
         match addr {
             0x0000..=0x1FFF => self.read_chr(addr),
-            0x2000..=0x3EFF => {
-                let addr = self.mirrored_address(addr);
-
-                self.vram[addr as usize]
-            }
+            0x2000..=0x3EFF => self.read_nametable(addr),
             0x3F00..=0x3FFF => {
                 let addr = (addr % 0x20) as usize;
 
@@ -729,19 +791,17 @@ impl Ppu {
 
         let palette_idx = self.pram[pixel as usize].bits_abs(0, 5);
 
-        let emphasis_bits = u8::from(self.mask).bits(5, 7) as usize;
-
-        self.palette.select_palette(emphasis_bits);
-
         let palette_idx = if self.mask.greyscale {
             palette_idx & 0x30
         } else {
             palette_idx
         };
 
-        let color = self.palette[palette_idx];
+        // Emphasis applies to every pixel, backdrop included, so mid-frame changes tint from the
+        // dot they're written on
+        let emphasis = self.mask.emphasis(self.region);
 
-        self.draw_pixel(Some(color), x, y);
+        self.pixels[x + y * 256] = palette_idx as u16 | (emphasis as u16) << 6;
     }
 
     fn spr_fetch_pt(&self, pt: u16, nt_byte: u8, offset: u16) -> u8 {
@@ -830,17 +890,19 @@ impl Ppu {
 
                 self.fetch_nt_byte();
 
-                self.cur_spr = if self.spr_idx < self.oam.sprites.len() {
-                    let (id, spr, _) = self.oam.sprites[self.spr_idx];
+                if rendering {
+                    self.oam.set_addr(0);
+                }
 
-                    if id == 0 {
-                        self.spr0_present = true;
-                    }
+                self.cur_spr = self.oam.sprites.get(self.spr_idx).copied();
 
-                    Some(spr)
-                } else {
-                    None
-                };
+                if self.spr_idx == 0 && self.cur_spr.is_some() {
+                    self.spr0_present = self.oam.sprite0_in_range;
+                }
+
+                if dot == 257 {
+                    self.fetch_extra_sprites();
+                }
             }
             // Garbage NT
             dot @ (257..=320) if dot % 8 == 3 => {
@@ -948,13 +1010,29 @@ impl Ppu {
             self.render();
             self.sx += 1;
 
-            if rendering && self.dot == 65 {
-                self.oam
-                    .calculate_sprites_on_scanline(self.scanline as usize + 1, self.ctrl.size);
+            if rendering {
+                // The pre-render scanline doesn't evaluate sprites, so none show on the first
+                // line
+                if self.scanline < 240 {
+                    self.oam.evaluate(self.dot, self.scanline, self.ctrl.size);
 
-                self.status.get_mut().spr_overflow |= self.oam.overflow;
+                    self.status.get_mut().spr_overflow |= self.oam.overflow;
 
-                self.spr_idx = 0;
+                    if self.dot == 256 {
+                        if self.no_sprite_limit {
+                            self.oam.find_extra_sprites(self.scanline, self.ctrl.size);
+                        } else {
+                            self.oam.extra_sprites.clear();
+                        }
+                    }
+                } else if self.dot == 65 {
+                    self.oam.sprites.clear();
+                    self.oam.extra_sprites.clear();
+                }
+
+                if self.dot == 65 {
+                    self.spr_idx = 0;
+                }
             }
         }
 
@@ -990,11 +1068,19 @@ impl Ppu {
                 self.v.update(addr);
             }
             339 => {
-                if rendering && self.odd {
+                // Only the NTSC PPU shortens odd frames
+                let skip = rendering && self.odd && self.region == Region::Ntsc;
+
+                if skip {
                     self.dot = 340;
                 }
                 self.odd = !self.odd;
 
+                // Each dot is 8 of the 12 color clock phases, so the next frame's signal starts
+                // at a different phase, unless the skipped dot evens it out
+                let dots = 341 * self.region.scanlines() as usize - skip as usize;
+                self.ntsc_phase = ((self.ntsc_phase as usize + dots * 8) % 12) as u8;
+
                 self.frame = self.frame.wrapping_add(1);
             }
             _ => {}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+use crate::region::Region;
 use crate::util::bit::Bit;
 
 #[derive(Clone, Copy, Serialize, Deserialize)]
@@ -27,6 +28,17 @@ impl Mask {
             greyscale: false,
         }
     }
+
+    /// The emphasis bits as an index into the palette table, with red as the lowest bit. PAL
+    /// and Dendy PPUs have the red and green bits swapped.
+    pub fn emphasis(&self, region: Region) -> usize {
+        let (red, green) = match region {
+            Region::Ntsc => (self.emphasize_red, self.emphasize_green),
+            Region::Pal | Region::Dendy => (self.emphasize_green, self.emphasize_red),
+        };
+
+        (self.emphasize_blue as usize) << 2 | (green as usize) << 1 | red as usize
+    }
 }
 
 impl From<Mask> for u8 {
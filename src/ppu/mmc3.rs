@@ -1,3 +1,4 @@
+use crate::bus::irq::{IrqLine, IrqSource};
 use crate::gui::DebugInfo;
 use egui::Ui;
 use serde::{Deserialize, Serialize};
@@ -6,7 +7,6 @@ use serde::{Deserialize, Serialize};
 pub struct Irq {
     pub counter: u8,
     pub enabled: bool,
-    pub occurred: bool,
     pub reload: u8,
     old_counter: u8,
     filter: usize,
@@ -17,14 +17,13 @@ impl Irq {
         Self {
             counter: 0,
             enabled: false,
-            occurred: false,
             reload: 0,
             old_counter: 0,
             filter: 0,
         }
     }
 
-    pub fn clock(&mut self) {
+    pub fn clock(&mut self, irq: &IrqLine) {
         self.old_counter = self.counter;
 
         if self.counter == 0 {
@@ -34,7 +33,7 @@ impl Irq {
         }
 
         if self.old_counter == 1 && self.enabled {
-            self.occurred = true;
+            irq.assert(IrqSource::Mapper);
         }
     }
 }
@@ -59,6 +59,22 @@ pub struct Sprite {
     pub x: u8,
 }
 
+impl Evaluation {
+    /// Moves to the next byte of OAM, and returns whether that wrapped around to sprite 0
+    fn next_byte(&mut self) -> bool {
+        self.m = (self.m + 1) % 4;
+
+        self.m == 0 && self.next_sprite()
+    }
+
+    /// Moves to the next sprite, and returns whether that wrapped around to sprite 0
+    fn next_sprite(&mut self) -> bool {
+        self.n = (self.n + 1) % 64;
+
+        self.n == 0
+    }
+}
+
 impl From<&[u8]> for Sprite {
     fn from(item: &[u8]) -> Self {
         let y = item[0];
@@ -75,13 +91,48 @@ impl From<&[u8]> for Sprite {
     }
 }
 
+fn cleared_secondary() -> [u8; 32] {
+    [0xFF; 32]
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Oam {
     #[serde(with = "BigArray")]
     pub(super) buffer: [u8; 256],
     pub(super) overflow: bool,
-    pub(super) sprites: Vec<(usize, Sprite, usize)>,
+    /// The sprites evaluation found for the next scanline, at most 8
+    pub(super) sprites: Vec<Sprite>,
+    /// Whether the first sprite evaluation looked at, normally sprite 0, is among `sprites`
+    #[serde(default)]
+    pub(super) sprite0_in_range: bool,
+    /// In-range sprites past the first 8, which the hardware drops. Only filled in when the
+    /// sprite limit is turned off.
+    #[serde(default)]
+    pub(super) extra_sprites: Vec<Sprite>,
     addr: Cell<u8>,
+    // The state of sprite evaluation, which steps through OAM a byte at a time on dots 65-256
+    #[serde(default)]
+    eval: Evaluation,
+    // Secondary OAM, which evaluation copies the in-range sprites into
+    #[serde(default = "cleared_secondary")]
+    secondary: [u8; 32],
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+struct Evaluation {
+    // Byte last read from OAM, which is what $2004 returns meanwhile
+    latch: u8,
+    // Sprite evaluation started at
+    first: u8,
+    // Sprite and byte within it being read. Together they're the OAM address.
+    n: u8,
+    m: u8,
+    // Next free byte of secondary OAM
+    secondary_addr: usize,
+    // Bytes of the current sprite left to copy, or to read after finding an overflow
+    remaining: u8,
+    // Every sprite has been looked at, or the overflow has been found, and evaluation idles
+    done: bool,
 }
 
 impl Oam {
@@ -90,7 +141,11 @@ impl Oam {
             buffer: [0; 256],
             overflow: false,
             sprites: Vec::with_capacity(8),
+            sprite0_in_range: false,
+            extra_sprites: Vec::new(),
             addr: Cell::new(0),
+            eval: Evaluation::default(),
+            secondary: cleared_secondary(),
         }
     }
 
@@ -98,30 +153,125 @@ impl Oam {
         self.addr.get()
     }
 
-    pub fn calculate_sprites_on_scanline(&mut self, scanline: usize, size: u8) {
-        let sprites = &mut self.sprites;
-        sprites.clear();
+    /// Runs sprite evaluation for one dot of a visible scanline: secondary OAM is cleared on
+    /// dots 1-64 and filled with the sprites that are on the next scanline on dots 65-256. OAM
+    /// is read on odd dots and secondary OAM written on even ones.
+    pub fn evaluate(&mut self, dot: u16, scanline: u16, size: u8) {
+        match dot {
+            1..=64 => {
+                // A byte every two dots
+                self.eval.latch = 0xFF;
+                self.secondary[(dot as usize - 1) / 2] = 0xFF;
+            }
+            65..=256 => {
+                if dot == 65 {
+                    let addr = self.addr.get();
 
-        self.overflow = false;
+                    self.eval = Evaluation {
+                        latch: 0xFF,
+                        first: addr >> 2,
+                        n: addr >> 2,
+                        m: addr & 3,
+                        ..Evaluation::default()
+                    };
+                    self.overflow = false;
+                    self.sprite0_in_range = false;
+                }
 
-        let scanline = scanline as i16;
+                if dot % 2 == 1 {
+                    self.eval.latch = self.buffer[(self.eval.n * 4 + self.eval.m) as usize];
+                } else {
+                    self.evaluate_byte(dot, scanline, size);
+                }
 
-        for spr_addr in (0..256).step_by(4) {
-            let spr_y = self.buffer[spr_addr] as i16 + 1;
-            let spr_yoff = scanline - spr_y;
+                if dot == 256 {
+                    self.sprites = self
+                        .secondary
+                        .chunks_exact(4)
+                        .take(self.eval.secondary_addr / 4)
+                        .map(Sprite::from)
+                        .collect();
+                }
+            }
+            _ => {}
+        }
+    }
 
-            let spr_height = (size / 8) as i16;
+    /// Finds the sprites on the next scanline that evaluation had no room for, in the order it
+    /// would have found them
+    pub fn find_extra_sprites(&mut self, scanline: u16, size: u8) {
+        self.extra_sprites.clear();
 
-            if spr_yoff >= 0 && spr_yoff < spr_height {
-                if sprites.len() == 8 {
-                    self.overflow = true;
-                    break;
-                }
+        if self.sprites.len() < 8 {
+            return;
+        }
+
+        let in_range = (0..64)
+            .map(|n| (self.eval.first as usize + n) % 64 * 4)
+            .filter(|&addr| {
+                scanline
+                    .checked_sub(self.buffer[addr] as u16)
+                    .is_some_and(|row| row < size as u16 / 8)
+            });
+
+        self.extra_sprites = in_range
+            .skip(8)
+            .map(|addr| Sprite::from(&self.buffer[addr..addr + 4]))
+            .collect();
+    }
+
+    fn evaluate_byte(&mut self, dot: u16, scanline: u16, size: u8) {
+        let eval = &mut self.eval;
+        let in_range = scanline
+            .checked_sub(eval.latch as u16)
+            .is_some_and(|row| row < size as u16 / 8);
+
+        if eval.done {
+            // Copies of OAM[n][0] that go nowhere
+            eval.n = (eval.n + 1) % 64;
+        } else if eval.secondary_addr < self.secondary.len() {
+            // Y is always copied, but only kept along with the rest of the sprite if it's in
+            // range
+            self.secondary[eval.secondary_addr] = eval.latch;
+
+            if eval.remaining > 0 {
+                eval.remaining -= 1;
+                eval.secondary_addr += 1;
+                eval.done = eval.next_byte();
+            } else if in_range {
+                self.sprite0_in_range |= dot == 66;
+                eval.remaining = 3;
+                eval.secondary_addr += 1;
+                eval.done = eval.next_byte();
+            } else {
+                eval.done = eval.next_sprite();
+            }
+        } else if eval.remaining > 0 {
+            eval.remaining -= 1;
+            eval.done = eval.next_byte() || eval.remaining == 0;
+        } else if in_range {
+            self.overflow = true;
+            eval.remaining = 3;
+            eval.done = eval.next_byte();
+        } else {
+            // The hardware bug: with secondary OAM full, m moves on along with n, so the
+            // following sprites are checked with their tile, attribute or X byte as Y
+            eval.m = (eval.m + 1) % 4;
+            eval.done = eval.next_sprite();
+        }
+    }
 
-                let sprite = Sprite::from(&self.buffer[spr_addr..spr_addr + 4]);
+    /// The value $2004 reads while the PPU is rendering, which is whatever sprite evaluation or
+    /// the sprite fetches are reading at `dot`
+    pub fn read_while_rendering(&self, dot: u16) -> u8 {
+        match dot {
+            1..=256 => self.eval.latch,
+            257..=320 => {
+                let offset = (dot - 257) as usize;
 
-                sprites.push((spr_addr, sprite, spr_yoff as usize));
+                self.secondary[offset / 8 * 4 + (offset % 8).min(3)]
             }
+            _ => self.secondary[0],
         }
     }
 
@@ -148,7 +298,8 @@ impl Oam {
     pub fn write(&mut self, data: u8) {
         let addr = self.addr.get();
 
-        self.buffer[addr as usize] = data;
+        // Bits 2-4 of the attribute byte don't exist and read back as 0
+        self.buffer[addr as usize] = if addr % 4 == 2 { data & 0xE3 } else { data };
 
         self.addr.replace((addr).wrapping_add(1));
     }
@@ -1,8 +1,75 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
 use std::ops::{Index, IndexMut};
+use std::{fs, io};
 
 use serde::{Deserialize, Serialize};
 use serde_big_array::BigArray;
 
+use crate::ntsc::{self, ATTENUATION};
+
+const BUILT_IN: &[u8] = include_bytes!("../../ntscpalette.pal");
+
+#[derive(Debug)]
+pub enum PaletteError {
+    Io(io::Error),
+    InvalidSize(usize),
+}
+
+impl Display for PaletteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaletteError::Io(error) => write!(f, "Unable to read palette: {error}"),
+            PaletteError::InvalidSize(size) => write!(
+                f,
+                "Palette is {size} bytes, expected 192 (64 colors) or 1536 (512 colors)"
+            ),
+        }
+    }
+}
+
+impl Error for PaletteError {}
+
+impl From<io::Error> for PaletteError {
+    fn from(error: io::Error) -> Self {
+        PaletteError::Io(error)
+    }
+}
+
+/// Where the colors of a `PaletteTable` come from
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum PaletteSource {
+    /// The palette compiled into the emulator
+    BuiltIn,
+    /// A .pal file with 64 RGB triplets, or 512 including every emphasis combination
+    File(String),
+    Generated(NtscParams),
+}
+
+/// Knobs of the NTSC palette generator, which decodes the PPU's composite signal levels
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct NtscParams {
+    /// Rotation of every hue, in degrees
+    pub hue: f32,
+    pub saturation: f32,
+    pub contrast: f32,
+    pub brightness: f32,
+    /// Gamma of the simulated TV, against the 2.2 of the display
+    pub gamma: f32,
+}
+
+impl Default for NtscParams {
+    fn default() -> Self {
+        Self {
+            hue: 0.0,
+            saturation: 1.0,
+            contrast: 1.0,
+            brightness: 1.0,
+            gamma: 1.8,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Rgba(pub u8, pub u8, pub u8, pub u8);
 
@@ -44,14 +111,30 @@ impl IndexMut<usize> for Palette {
 pub struct PaletteTable {
     #[serde(with = "BigArray")]
     palettes: [Palette; 8],
-    selected: usize,
 }
 
 impl PaletteTable {
     pub fn new() -> Self {
-        let buf = include_bytes!("../../ntscpalette.pal");
+        Self::from_pal(BUILT_IN).expect("the built-in palette is 512 colors")
+    }
+
+    pub fn load(source: &PaletteSource) -> Result<Self, PaletteError> {
+        match source {
+            PaletteSource::BuiltIn => Ok(Self::new()),
+            PaletteSource::File(path) => Self::from_pal(&fs::read(path)?),
+            PaletteSource::Generated(params) => Ok(Self::generate(params)),
+        }
+    }
+
+    /// Parses the contents of a .pal file. A file with only 64 colors gets its emphasis
+    /// variants by darkening the channels that aren't emphasized.
+    pub fn from_pal(buf: &[u8]) -> Result<Self, PaletteError> {
         let mut palette_arr: [Palette; 8] = [Palette([Rgba(0, 0, 0, 0); 64]); 8];
 
+        if buf.len() != 64 * 3 && buf.len() != 512 * 3 {
+            return Err(PaletteError::InvalidSize(buf.len()));
+        }
+
         buf.chunks_exact(64 * 3)
             .enumerate()
             .for_each(|(palette_number, palette)| {
@@ -66,21 +149,78 @@ impl PaletteTable {
                     })
             });
 
+        if buf.len() == 64 * 3 {
+            let base = palette_arr[0];
+
+            for (emphasis, palette) in palette_arr.iter_mut().enumerate().skip(1) {
+                for (rgba, &Rgba(r, g, b, a)) in palette.0.iter_mut().zip(base.0.iter()) {
+                    // Emphasis bits are red, green and blue from the lowest
+                    let [r, g, b] = [r, g, b].map(|channel| channel as f32);
+                    let [r, g, b] = [(r, 0), (g, 1), (b, 2)].map(|(channel, bit)| {
+                        let darkened = (0..3)
+                            .filter(|&other| other != bit && emphasis & (1 << other) != 0)
+                            .count();
+
+                        (channel * ATTENUATION.powi(darkened as i32)) as u8
+                    });
+
+                    *rgba = Rgba(r, g, b, a);
+                }
+            }
+        }
+
+        Ok(Self {
+            palettes: palette_arr,
+        })
+    }
+
+    /// Generates all 512 colors by decoding the composite signal the PPU would output for each
+    /// of them, in the way of Bisqwit's NTSC palette generator
+    pub fn generate(params: &NtscParams) -> Self {
+        let mut palette_arr: [Palette; 8] = [Palette([Rgba(0, 0, 0, 0); 64]); 8];
+
+        for (emphasis, palette) in palette_arr.iter_mut().enumerate() {
+            for (idx, rgba) in palette.0.iter_mut().enumerate() {
+                let pixel = (emphasis << 6 | idx) as u16;
+
+                let (mut y, mut i, mut q) = (0.0, 0.0, 0.0);
+
+                // The color wave is 12 samples long, each phase being a different hue
+                for phase in 0..12 {
+                    let v = ntsc::level(ntsc::signal(pixel, phase), params) / 12.0;
+                    let angle = ntsc::hue_angle(phase, params);
+
+                    y += v;
+                    i += v * angle.cos();
+                    q += v * angle.sin();
+                }
+
+                *rgba = ntsc::yiq_to_rgba(y, i, q, params);
+            }
+        }
+
         Self {
             palettes: palette_arr,
-            selected: 0,
         }
     }
 
-    pub fn select_palette(&mut self, idx: usize) {
-        self.selected = idx;
+    /// The color of a raw pixel from the PPU, a color index with the emphasis bits above it
+    pub fn rgba(&self, pixel: u16) -> Rgba {
+        self.palettes[(pixel >> 6) as usize & 7][pixel as usize & 0x3F]
+    }
+}
+
+impl Default for PaletteTable {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
+/// Colors without emphasis
 impl Index<u8> for PaletteTable {
     type Output = Rgba;
 
     fn index(&self, index: u8) -> &Self::Output {
-        &self.palettes[self.selected][index as usize]
+        &self.palettes[0][index as usize]
     }
 }
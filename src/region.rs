@@ -0,0 +1,99 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cartridge::Timing;
+
+/// The console variant being emulated, which decides the clock speeds and frame timing
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum Region {
+    #[default]
+    Ntsc,
+    Pal,
+    /// Famiclones like the Dendy, which pair PAL video timing with an NTSC-like CPU/PPU ratio
+    Dendy,
+}
+
+impl Region {
+    /// The region a cartridge was made for. Multi-region games run as NTSC.
+    pub fn from_timing(timing: Timing) -> Self {
+        match timing {
+            Timing::Ntsc | Timing::MultiRegion => Region::Ntsc,
+            Timing::Pal => Region::Pal,
+            Timing::Dendy => Region::Dendy,
+        }
+    }
+
+    /// CPU cycles per second
+    pub fn clockrate(self) -> u32 {
+        match self {
+            Region::Ntsc => 1_789_773,
+            Region::Pal => 1_662_607,
+            Region::Dendy => 1_773_448,
+        }
+    }
+
+    /// PPU dots per CPU cycle, as a `(dots, cycles)` fraction
+    pub fn ppu_dots_per_cycle(self) -> (u32, u32) {
+        match self {
+            Region::Ntsc | Region::Dendy => (3, 1),
+            Region::Pal => (16, 5),
+        }
+    }
+
+    /// CPU cycles per frame, as a `(cycles, frames)` fraction
+    pub fn cycles_per_frame(self) -> (usize, usize) {
+        let dots = 341 * self.scanlines() as usize;
+        let (ppu_dots, cpu_cycles) = self.ppu_dots_per_cycle();
+
+        (dots * cpu_cycles as usize, ppu_dots as usize)
+    }
+
+    /// Scanlines per frame, including the pre-render scanline
+    pub fn scanlines(self) -> u16 {
+        match self {
+            Region::Ntsc => 262,
+            Region::Pal | Region::Dendy => 312,
+        }
+    }
+
+    /// Frames per second
+    pub fn frame_rate(self) -> f64 {
+        let (cycles, frames) = self.cycles_per_frame();
+
+        self.clockrate() as f64 * frames as f64 / cycles as f64
+    }
+
+    /// The scanline vblank starts on
+    pub fn vblank_scanline(self) -> u16 {
+        match self {
+            Region::Ntsc | Region::Pal => 241,
+            // The Dendy pads the post-render period instead, keeping vblank 20 scanlines long
+            Region::Dendy => 291,
+        }
+    }
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Region::Ntsc => write!(f, "NTSC"),
+            Region::Pal => write!(f, "PAL"),
+            Region::Dendy => write!(f, "Dendy"),
+        }
+    }
+}
+
+impl FromStr for Region {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ntsc" => Ok(Region::Ntsc),
+            "pal" => Ok(Region::Pal),
+            "dendy" => Ok(Region::Dendy),
+            _ => Err(format!("unknown region `{s}`, expected ntsc, pal or dendy")),
+        }
+    }
+}
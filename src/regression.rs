@@ -0,0 +1,200 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bus::mapper::MapperTrait;
+use crate::controller::{Btn, Input};
+use crate::core::EmulatorCore;
+use crate::region::Region;
+use crate::util::Config;
+
+/// A JSON list of ROMs to run and the hash of the picture each is expected to end on
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Manifest {
+    pub entries: Vec<ManifestEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ManifestEntry {
+    /// Path to the ROM, relative to the manifest
+    pub rom: String,
+    pub frames: usize,
+    /// Button changes, applied in order of `frame`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<InputEvent>,
+    /// Region to run in, overriding the one in the ROM header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<Region>,
+    /// Golden hash of the PPU's raw pixels after `frames` frames, missing for new entries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+/// Holds `buttons` on the first controller and `buttons2` on the second (and nothing else) from
+/// `frame` until the next event
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InputEvent {
+    pub frame: usize,
+    #[serde(default)]
+    pub buttons: Vec<Btn>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub buttons2: Vec<Btn>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    Matched,
+    Changed { expected: String, actual: String },
+    New(String),
+    Error(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct EntryResult {
+    pub rom: String,
+    pub outcome: Outcome,
+}
+
+impl Display for EntryResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.outcome {
+            Outcome::Matched => write!(f, "ok       {}", self.rom),
+            Outcome::Changed { expected, actual } => {
+                write!(
+                    f,
+                    "CHANGED  {}: expected {expected}, got {actual}",
+                    self.rom
+                )
+            }
+            Outcome::New(actual) => write!(f, "new      {}: {actual}", self.rom),
+            Outcome::Error(error) => write!(f, "ERROR    {}: {error}", self.rom),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RegressionReport {
+    pub results: Vec<EntryResult>,
+}
+
+impl RegressionReport {
+    /// Whether every entry had a golden hash and still matches it
+    pub fn passed(&self) -> bool {
+        self.results
+            .iter()
+            .all(|result| result.outcome == Outcome::Matched)
+    }
+}
+
+impl Display for RegressionReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (mut matched, mut changed, mut new, mut errors) = (0, 0, 0, 0);
+
+        for result in &self.results {
+            writeln!(f, "{result}")?;
+
+            match result.outcome {
+                Outcome::Matched => matched += 1,
+                Outcome::Changed { .. } => changed += 1,
+                Outcome::New(_) => new += 1,
+                Outcome::Error(_) => errors += 1,
+            }
+        }
+
+        write!(
+            f,
+            "{matched} matched, {changed} changed, {new} new, {errors} failed to run"
+        )
+    }
+}
+
+/// Runs every entry of the manifest at `path`. With `update`, the manifest is rewritten with the
+/// hashes that were produced so changed and new entries become the new golden values.
+pub fn run_manifest(path: &str, update: bool) -> Result<RegressionReport, Box<dyn Error>> {
+    let mut manifest: Manifest = serde_json::from_str(&fs::read_to_string(path)?)?;
+    let base_dir = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+
+    let mut report = RegressionReport::default();
+
+    for entry in &mut manifest.entries {
+        let rom = base_dir.join(&entry.rom).to_string_lossy().into_owned();
+
+        let outcome = match run_entry(&rom, entry) {
+            Ok(actual) => match &entry.hash {
+                Some(expected) if *expected == actual => Outcome::Matched,
+                Some(expected) => Outcome::Changed {
+                    expected: expected.clone(),
+                    actual,
+                },
+                None => Outcome::New(actual),
+            },
+            Err(error) => Outcome::Error(error.to_string()),
+        };
+
+        if update {
+            if let Outcome::Changed { actual, .. } | Outcome::New(actual) = &outcome {
+                entry.hash = Some(actual.clone());
+            }
+        }
+
+        report.results.push(EntryResult {
+            rom: entry.rom.clone(),
+            outcome,
+        });
+    }
+
+    if update {
+        fs::write(path, serde_json::to_string_pretty(&manifest)? + "\n")?;
+    }
+
+    Ok(report)
+}
+
+/// Runs a single ROM from power on and returns the hash of its last frame
+pub fn run_entry(rom: &str, entry: &ManifestEntry) -> Result<String, Box<dyn Error>> {
+    let mut config = Config::new(rom);
+    config.region = entry.region;
+    config.no_battery = true;
+
+    let mut core = EmulatorCore::new(&config, false)?;
+
+    core.cpu.reset();
+
+    let mut inputs = entry.inputs.clone();
+    inputs.sort_by_key(|event| event.frame);
+
+    let mut inputs = inputs.iter().peekable();
+    let mut input = [Input::new(); 2];
+
+    for frame in 0..entry.frames {
+        while let Some(event) = inputs.next_if(|event| event.frame <= frame) {
+            input = [Input::new(); 2];
+
+            for (input, buttons) in input.iter_mut().zip([&event.buttons, &event.buttons2]) {
+                for &button in buttons {
+                    input.press(button);
+                }
+            }
+        }
+
+        core.run_frame(input)?;
+    }
+
+    Ok(hash_pixels(&core.cpu.bus.ppu().pixels))
+}
+
+/// 64-bit FNV-1a over the PPU's raw pixels, as hex. Hashing the color indices rather than RGBA
+/// keeps the golden values independent of the palette and video filter.
+pub fn hash_pixels(pixels: &[u16]) -> String {
+    let hash = pixels
+        .iter()
+        .flat_map(|pixel| pixel.to_le_bytes())
+        .fold(0xCBF2_9CE4_8422_2325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01B3)
+        });
+
+    format!("{hash:016x}")
+}
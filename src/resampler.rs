@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+use crate::SAMPLERATE;
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct IIRFilter {
+    pub output: f32,
+    b: f32,
+}
+
+impl IIRFilter {
+    pub fn new(d: f32) -> Self {
+        Self {
+            output: 0.0,
+            b: 1.0 - d,
+        }
+    }
+
+    pub fn filter(&mut self, input: f32) -> f32 {
+        self.output += self.b * (input - self.output);
+
+        self.output
+    }
+}
+
+/// Low-pass filters the per-cycle APU output and decimates it to `SAMPLERATE`
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Resampler {
+    pub output: Option<[f32; 6]>,
+    clockrate: u32,
+    counter: u32,
+    filters: [IIRFilter; 6],
+}
+
+impl Resampler {
+    pub fn new(clockrate: u32) -> Self {
+        Self {
+            output: None,
+            clockrate,
+            counter: 0,
+            filters: [IIRFilter::new(0.9); 6],
+        }
+    }
+
+    pub fn push_sample(&mut self, audio_samples: &[f32; 6]) -> Option<f32> {
+        for (channel_filter, &input) in self.filters.iter_mut().zip(audio_samples.iter()) {
+            channel_filter.filter(input);
+        }
+
+        let sample = self.counter + SAMPLERATE > self.clockrate;
+
+        self.counter = (self.counter + SAMPLERATE) % self.clockrate;
+
+        if !sample {
+            return None;
+        }
+
+        let [pulse1, pulse2, triangle, noise, dmc, output] =
+            self.filters.map(|filter| filter.output);
+
+        self.output.replace([
+            (pulse1 / 15.0) * 2.0 - 1.0,
+            (pulse2 / 15.0) * 2.0 - 1.0,
+            (triangle / 15.0) * 2.0 - 1.0,
+            (noise / 15.0) * 2.0 - 1.0,
+            (dmc / 128.0) * 2.0 - 1.0,
+            output * 2.0 - 1.0,
+        ]);
+
+        Some(output)
+    }
+
+    pub fn _set_clockrate(&mut self, clockrate: u32) {
+        self.clockrate = clockrate;
+    }
+}
@@ -1,28 +1,43 @@
 use std::error::Error;
 
 use byte_slice_cast::AsByteSlice;
-use sdl2::pixels::PixelFormatEnum;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
 use sdl2::render::{Texture, WindowCanvas};
 use sdl2::Sdl;
 
-const WIDTH: u32 = 800;
-const HEIGHT: u32 = 600;
+use crate::display::{DisplayOptions, Scaler};
+
+// The window opens at this multiple of the picture's size
+const WINDOW_SCALE: f64 = 3.0;
 
 pub(super) struct Screen {
     pub fb: Texture,
+    cropped: Vec<u32>,
+    options: DisplayOptions,
+    scaled: Vec<u32>,
     screen: WindowCanvas,
+    // Size of `fb`
+    size: (usize, usize),
 }
 
 impl Screen {
-    pub fn new(sdl_context: &Sdl) -> Result<Self, Box<dyn Error>> {
+    pub fn new(sdl_context: &Sdl, options: DisplayOptions) -> Result<Self, Box<dyn Error>> {
+        let (width, height) = options.aspect_ratio();
+
         let canvas = {
             let video_subsystem = sdl_context.video()?;
 
             sdl_context.mouse().show_cursor(false);
 
             let window = video_subsystem
-                .window("NES Emulator", WIDTH, HEIGHT)
+                .window(
+                    "NES Emulator",
+                    (width * WINDOW_SCALE).round() as u32,
+                    (height * WINDOW_SCALE).round() as u32,
+                )
                 .position_centered()
+                .resizable()
                 .opengl()
                 .build()?;
 
@@ -39,17 +54,71 @@ impl Screen {
                     240,
                 )?
             },
+            cropped: Vec::new(),
+            options,
+            scaled: Vec::new(),
             screen: canvas,
+            size: (256, 240),
         })
     }
 
-    pub fn render(&mut self, fb: &[u32]) -> Result<(), Box<dyn Error>> {
-        self.fb.update(None, fb.as_byte_slice(), 256 * 4)?;
+    /// Presents a frame that is `width` pixels wide and 240 high, cropped and scaled as set in
+    /// the display options and centered in the window
+    pub fn render(&mut self, fb: &[u32], width: usize) -> Result<(), Box<dyn Error>> {
+        let (width, height) = self.options.crop(fb, width, &mut self.cropped);
+
+        let scaler = self.options.scaler;
+        scaler.scale(&self.cropped, width, height, &mut self.scaled);
+
+        let size = (width * scaler.factor(), height * scaler.factor());
+
+        if size != self.size {
+            self.fb = self.screen.texture_creator().create_texture_streaming(
+                Some(PixelFormatEnum::ABGR8888),
+                size.0 as u32,
+                size.1 as u32,
+            )?;
+            self.size = size;
+        }
+
+        self.fb
+            .update(None, self.scaled.as_byte_slice(), size.0 * 4)?;
+
+        let dst = self.picture_rect()?;
 
-        self.screen.copy(&self.fb, None, None)?;
+        self.screen.set_draw_color(Color::BLACK);
+        self.screen.clear();
+        self.screen.copy(&self.fb, None, dst)?;
 
         self.screen.present();
 
         Ok(())
     }
+
+    /// The largest area with the picture's aspect ratio that fits the window. With nearest
+    /// scaling, lines are only ever shown at whole multiples.
+    fn picture_rect(&self) -> Result<Rect, Box<dyn Error>> {
+        let (window_width, window_height) = self.screen.output_size()?;
+        let (width, height) = self.options.aspect_ratio();
+
+        let scale = (window_width as f64 / width).min(window_height as f64 / height);
+
+        let scale = if self.options.scaler == Scaler::Nearest && scale >= 1.0 {
+            scale.floor()
+        } else {
+            scale
+        };
+
+        let (width, height) = (
+            (width * scale).round() as u32,
+            (height * scale).round() as u32,
+        );
+
+        Ok(Rect::new(
+            ((window_width - width.min(window_width)) / 2) as i32,
+            ((window_height - height.min(window_height)) / 2) as i32,
+            width.max(1),
+            height.max(1),
+        ))
+    }
 }
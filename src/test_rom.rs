@@ -0,0 +1,177 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use crate::bus::mapper::MapperTrait;
+use crate::controller::Input;
+use crate::core::EmulatorCore;
+
+// blargg's test ROMs report through PRG-RAM:
+// $6000 holds the status, $6001-$6003 a signature and $6004 a zero-terminated message
+const STATUS_ADDR: u16 = 0x6000;
+const SIGNATURE_ADDR: u16 = 0x6001;
+const SIGNATURE: [u8; 3] = [0xDE, 0xB0, 0x61];
+const TEXT_ADDR: u16 = 0x6004;
+
+const STATUS_RUNNING: u8 = 0x80;
+const STATUS_RESET: u8 = 0x81;
+
+// The ROM wants reset to be pressed at least 100 ms after requesting it
+const RESET_DELAY_FRAMES: usize = 6;
+
+pub const DEFAULT_TIMEOUT_FRAMES: usize = 60 * 60;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TestStatus {
+    Passed,
+    Failed(u8),
+    /// The CPU hit a KIL opcode at this address
+    Jammed(u16),
+    TimedOut(Timeout),
+}
+
+/// The limit a test ROM ran into without reporting a result
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Timeout {
+    Frames,
+    Cycles,
+}
+
+#[derive(Clone, Debug)]
+pub struct TestReport {
+    /// CPU cycles the test ran for
+    pub cycles: u64,
+    pub frames: usize,
+    pub message: String,
+    pub status: TestStatus,
+}
+
+impl TestReport {
+    pub fn passed(&self) -> bool {
+        self.status == TestStatus::Passed
+    }
+}
+
+impl Display for TestReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.status {
+            TestStatus::Passed => write!(f, "PASSED")?,
+            TestStatus::Failed(code) => write!(f, "FAILED (code {code})")?,
+            TestStatus::Jammed(pc) => write!(f, "CPU JAMMED at ${pc:04X}")?,
+            TestStatus::TimedOut(Timeout::Frames) => {
+                write!(f, "TIMED OUT after {} frames", self.frames)?
+            }
+            TestStatus::TimedOut(Timeout::Cycles) => write!(
+                f,
+                "TIMED OUT after {} CPU cycles ({} frames)",
+                self.cycles, self.frames
+            )?,
+        }
+
+        let message = self.message.trim();
+
+        if !message.is_empty() {
+            write!(f, "\n{message}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs a test ROM without input until it reports a result through $6000, or until `max_frames`
+/// frames or `max_cycles` CPU cycles have elapsed. Cycles are checked after each frame. The core
+/// is expected to be freshly created with `no_battery` set, and reset.
+pub fn run_test_rom(
+    core: &mut EmulatorCore,
+    max_frames: usize,
+    max_cycles: Option<u64>,
+) -> Result<TestReport, Box<dyn Error>> {
+    let start = core.cpu.bus.bus().cycle;
+
+    // Don't pick up a stale result from battery-backed RAM
+    for offset in 0..SIGNATURE.len() as u16 {
+        core.cpu.bus.write_u8(SIGNATURE_ADDR + offset, 0);
+    }
+
+    let mut reset_countdown = None;
+
+    for frame in 1..=max_frames {
+        core.run_frame([Input::new(); 2])?;
+
+        let cycles = core.cpu.bus.bus().cycle - start;
+
+        if let Some(pc) = core.cpu.jammed {
+            return Ok(TestReport {
+                cycles,
+                frames: frame,
+                message: String::new(),
+                status: TestStatus::Jammed(pc),
+            });
+        }
+
+        if max_cycles.is_some_and(|max_cycles| cycles >= max_cycles) {
+            return Ok(timed_out(core, cycles, frame, Timeout::Cycles));
+        }
+
+        if !has_signature(core) {
+            continue;
+        }
+
+        match core.cpu.bus.read_u8(STATUS_ADDR) {
+            STATUS_RUNNING => {}
+            STATUS_RESET => match reset_countdown {
+                None => reset_countdown = Some(RESET_DELAY_FRAMES),
+                Some(0) => {
+                    reset_countdown = None;
+                    core.cpu.reset();
+                }
+                Some(frames) => reset_countdown = Some(frames - 1),
+            },
+            status => {
+                return Ok(TestReport {
+                    cycles,
+                    frames: frame,
+                    message: read_message(core),
+                    status: if status == 0 {
+                        TestStatus::Passed
+                    } else {
+                        TestStatus::Failed(status)
+                    },
+                })
+            }
+        }
+    }
+
+    let cycles = core.cpu.bus.bus().cycle - start;
+
+    Ok(timed_out(core, cycles, max_frames, Timeout::Frames))
+}
+
+fn timed_out(core: &mut EmulatorCore, cycles: u64, frames: usize, timeout: Timeout) -> TestReport {
+    let message = if has_signature(core) {
+        read_message(core)
+    } else {
+        String::new()
+    };
+
+    TestReport {
+        cycles,
+        frames,
+        message,
+        status: TestStatus::TimedOut(timeout),
+    }
+}
+
+fn has_signature(core: &mut EmulatorCore) -> bool {
+    (0..SIGNATURE.len() as u16)
+        .map(|offset| core.cpu.bus.read_u8(SIGNATURE_ADDR + offset))
+        .eq(SIGNATURE)
+}
+
+fn read_message(core: &mut EmulatorCore) -> String {
+    let bytes = (TEXT_ADDR..0x8000)
+        .map(|addr| core.cpu.bus.read_u8(addr))
+        .take_while(|&byte| byte != 0)
+        .collect::<Vec<u8>>();
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
@@ -2,21 +2,66 @@ pub mod bit;
 pub mod shift_reg;
 
 use serde::{Deserialize, Serialize};
-use std::fs::File;
+
+use crate::display::DisplayOptions;
+use crate::ntsc::NtscPreset;
+use crate::region::Region;
+use std::error::Error;
+use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::Path;
 
+/// Settings for a run. A JSON config file can hold any of them except the ROM, which always comes
+/// from the command line.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Config {
+    #[serde(default, skip_serializing)]
     pub filename: String,
+    /// Overrides the region from the ROM header
+    #[serde(default)]
+    pub region: Option<Region>,
+    /// .pal file to use instead of the built-in palette
+    #[serde(default)]
+    pub palette: Option<String>,
+    /// Decodes the picture like a TV on this kind of connection, instead of using the palette
+    /// as is
+    #[serde(default)]
+    pub filter: Option<NtscPreset>,
+    #[serde(default)]
+    pub display: DisplayOptions,
+    /// Draws every sprite on a scanline, doing away with the flicker of the 8 sprite limit
+    #[serde(default)]
+    pub no_sprite_limit: bool,
+    /// Bindings file to use instead of the default keys and gamepad buttons
+    #[serde(default)]
+    pub bindings: Option<String>,
+    /// Starts with battery-backed RAM cleared instead of loading it from the .sav file, so
+    /// headless runs don't depend on what's on disk
+    #[serde(skip)]
+    pub no_battery: bool,
 }
 
 impl Config {
     pub fn new(filename: &str) -> Config {
         Config {
             filename: String::from(filename),
+            region: None,
+            palette: None,
+            filter: None,
+            display: DisplayOptions::default(),
+            no_sprite_limit: false,
+            bindings: None,
+            no_battery: false,
         }
     }
+
+    /// Reads the config file at `path` for running `filename`
+    pub fn from_file(path: &str, filename: &str) -> Result<Config, Box<dyn Error>> {
+        let mut config: Config = serde_json::from_str(&fs::read_to_string(path)?)?;
+        config.filename = String::from(filename);
+
+        Ok(config)
+    }
 }
 
 pub fn crosses_page(src: u16, offset: i32) -> bool {
@@ -26,17 +71,21 @@ pub fn crosses_page(src: u16, offset: i32) -> bool {
     !(0..=0xFF).contains(&target)
 }
 
-pub fn load_ram(filename: &str) -> [u8; 0x2000] {
+pub fn load_ram(filename: &str, ram: &mut [u8]) {
     let mut name = Path::new(filename).to_path_buf();
 
     name.set_extension("sav");
 
     if let Ok(mut save_file) = File::open(name) {
-        let mut buf: [u8; 0x2000] = [0; 0x2000];
+        let mut buf = Vec::with_capacity(ram.len());
 
-        save_file.read(&mut buf).map_or([0; 0x2000], |_| buf)
-    } else {
-        [0; 0x2000]
+        if save_file.read_to_end(&mut buf).is_ok() {
+            let len = buf.len().min(ram.len());
+
+            ram[..len].copy_from_slice(&buf[..len]);
+        } else {
+            eprintln!("WARNING: Failed to load RAM from disk");
+        }
     }
 }
 
@@ -0,0 +1,44 @@
+use crate::ppu::PaletteTable;
+
+/// Width of the frames the PPU outputs. Every frame is 240 lines high.
+pub const PPU_WIDTH: usize = 256;
+
+/// The stage that turns the PPU's raw pixels (color indices with the emphasis bits above them)
+/// into RGBA for display
+pub trait VideoFilter: Send {
+    /// Width of the frames `apply` produces
+    fn width(&self) -> usize;
+
+    /// Converts a 256x240 frame. `phase` is the color clock phase the frame started on, for
+    /// filters that simulate the analog signal.
+    fn apply(&mut self, pixels: &[u16], phase: u8) -> &[u32];
+}
+
+/// Looks every pixel up in a palette, as the PPU's colors are usually shown
+pub struct PaletteFilter {
+    palette: PaletteTable,
+    output: Vec<u32>,
+}
+
+impl PaletteFilter {
+    pub fn new(palette: PaletteTable) -> Self {
+        Self {
+            palette,
+            output: vec![0; PPU_WIDTH * 240],
+        }
+    }
+}
+
+impl VideoFilter for PaletteFilter {
+    fn width(&self) -> usize {
+        PPU_WIDTH
+    }
+
+    fn apply(&mut self, pixels: &[u16], _phase: u8) -> &[u32] {
+        for (rgba, &pixel) in self.output.iter_mut().zip(pixels) {
+            *rgba = self.palette.rgba(pixel).into();
+        }
+
+        &self.output
+    }
+}